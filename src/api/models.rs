@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use chrono;
 
@@ -29,15 +29,25 @@ pub struct Tracker {
 pub struct Shipment {
     #[serde(rename = "statusMilestone")]
     pub status_milestone: String,
+    #[serde(default)]
+    pub delivery: Option<Delivery>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize)]
+pub struct Delivery {
+    #[serde(rename = "estimatedDeliveryDate", default)]
+    pub estimated_delivery_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Event {
     pub status: Option<String>,
     #[serde(rename = "occurrenceDatetime", deserialize_with = "deserialize_datetime")]
     pub occurrence_datetime: chrono::DateTime<chrono::Utc>,
     #[serde(rename = "statusMilestone")]
     pub status_milestone: String,
+    #[serde(rename = "courierCode", default)]
+    pub courier_code: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -80,6 +90,46 @@ impl StatusMilestone {
         }
     }
 
+    pub fn icon_name(&self) -> &'static str {
+        match self {
+            Self::Pending => "content-loading-symbolic",
+            Self::InfoReceived => "dialog-information-symbolic",
+            Self::InTransit => "mail-send-symbolic",
+            Self::OutForDelivery => "find-location-symbolic",
+            Self::FailedAttempt => "action-unavailable-symbolic",
+            Self::AvailableForPickup => "mark-location-symbolic",
+            Self::Delivered => "emblem-ok-symbolic",
+            Self::Exception => "dialog-warning-symbolic",
+        }
+    }
+
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            Self::Pending => "status-pending",
+            Self::InfoReceived => "status-info-received",
+            Self::InTransit => "status-in-transit",
+            Self::OutForDelivery => "status-out-for-delivery",
+            Self::FailedAttempt => "status-failed-attempt",
+            Self::AvailableForPickup => "status-available-for-pickup",
+            Self::Delivered => "status-delivered",
+            Self::Exception => "status-exception",
+        }
+    }
+
+    /// How urgently the package needs a look, higher is more urgent.
+    pub fn severity(&self) -> u8 {
+        match self {
+            Self::Delivered => 0,
+            Self::Pending => 1,
+            Self::InfoReceived => 2,
+            Self::InTransit => 3,
+            Self::OutForDelivery => 4,
+            Self::AvailableForPickup => 5,
+            Self::FailedAttempt => 6,
+            Self::Exception => 7,
+        }
+    }
+
     pub fn order(&self) -> u8 {
         match self {
             Self::Pending => 0,
@@ -102,19 +152,75 @@ impl StatusMilestone {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TrackingInfo {
     pub id_ship: String,
     pub label: String,
-    pub status: String,
     pub events: Vec<Event>,
     pub timeline: Vec<Timeline>,
     pub url: Option<String>,
     pub has_error: bool,
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub estimated_delivery: Option<chrono::DateTime<chrono::Utc>>,
+    /// Ship24's current milestone for the whole shipment.
+    #[serde(default)]
+    pub shipment_milestone: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+impl TrackingInfo {
+    pub fn unavailable(id_ship: &str, message: &str) -> Self {
+        Self {
+            id_ship: id_ship.to_string(),
+            label: "Status unknown".to_string(),
+            events: Vec::new(),
+            timeline: Vec::new(),
+            url: None,
+            has_error: true,
+            error_message: Some(message.to_string()),
+            estimated_delivery: None,
+            shipment_milestone: None,
+        }
+    }
+
+    /// Where the shipment stands now: Ship24's shipment milestone, or the one of the most recent
+    /// event for info saved before it was kept. A past exception doesn't outlive a later delivery.
+    pub fn milestone(&self) -> StatusMilestone {
+        self.shipment_milestone
+            .as_deref()
+            .or_else(|| self.latest_event().map(|event| event.status_milestone.as_str()))
+            .map_or(StatusMilestone::Pending, StatusMilestone::from_str)
+    }
+
+    /// The name of the current milestone, "Unknown" when tracking failed.
+    pub fn status(&self) -> &'static str {
+        if self.has_error {
+            "Unknown"
+        } else {
+            self.milestone().to_string()
+        }
+    }
+
+    pub fn latest_event(&self) -> Option<&Event> {
+        self.events.iter().max_by_key(|e| e.occurrence_datetime)
+    }
+
+    pub fn courier(&self) -> Option<&str> {
+        self.latest_event()
+            .and_then(|e| e.courier_code.as_deref())
+            .or_else(|| self.events.iter().find_map(|e| e.courier_code.as_deref()))
+    }
+
+    pub fn delivered_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.events
+            .iter()
+            .filter(|e| StatusMilestone::from_str(&e.status_milestone) == StatusMilestone::Delivered)
+            .map(|e| e.occurrence_datetime)
+            .max()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Timeline {
     pub short_label: String,
     pub status: bool,
@@ -139,17 +245,23 @@ pub enum TrackingError {
     ParseError(#[from] serde_json::Error),
 }
 
+pub fn parse_datetime(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&chrono::Utc));
+    }
+    if let Ok(naive_dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+        return Some(chrono::DateTime::from_naive_utc_and_offset(naive_dt, chrono::Utc));
+    }
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|naive_dt| chrono::DateTime::from_naive_utc_and_offset(naive_dt, chrono::Utc))
+}
+
 pub fn deserialize_datetime<'de, D>(deserializer: D) -> Result<chrono::DateTime<chrono::Utc>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    
-    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&s) {
-        return Ok(dt.with_timezone(&chrono::Utc));
-    }
-    
-    chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S")
-        .map(|naive_dt| chrono::DateTime::from_naive_utc_and_offset(naive_dt, chrono::Utc))
-        .map_err(|e| serde::de::Error::custom(format!("Failed to parse datetime: {}", e)))
-} 
\ No newline at end of file
+    parse_datetime(&s).ok_or_else(|| serde::de::Error::custom(format!("Failed to parse datetime: {}", s)))
+}
@@ -1,4 +1,5 @@
 use super::models::*;
+use crate::tracking_number::detected_courier_code;
 use dotenvy_macro::dotenv;
 use std::time::Duration;
 use std::sync::Arc;
@@ -23,34 +24,38 @@ impl TrackingClient {
         }
     }
 
-    pub async fn process_tracking_numbers(&self, input: &str) -> Vec<TrackingInfo> {
-        let numbers: Vec<_> = input
-            .lines()
-            .map(str::trim)
-            .filter(|l| !l.is_empty())
-            .collect();
-
+    /// Tracks each number and pairs the result with the number it was requested for,
+    /// since Ship24 may report a normalized `id_ship`. Numbers come with an optional
+    /// Ship24 courier code, otherwise the carrier is detected from the number itself.
+    pub async fn track_numbers(&self, numbers: &[(String, Option<String>)]) -> Vec<(String, TrackingInfo)> {
         let mut results = Vec::with_capacity(numbers.len());
         let mut tasks = Vec::new();
 
         for chunk in numbers.chunks(10) {
             let mut chunk_tasks = Vec::new();
-            for &number in chunk {
-                let number = number.to_string();
+            for (number, courier_code) in chunk {
+                let number = number.clone();
                 let client = self.client.clone();
                 let api_key = self.api_key.clone();
-                chunk_tasks.push(tokio::spawn(async move {
+                let mut request = serde_json::json!({
+                    "trackingNumber": &number,
+                    "settings": {
+                        "restrictTrackingToCourierCode": false
+                    }
+                });
+                let courier_code = courier_code
+                    .as_deref()
+                    .or_else(|| detected_courier_code(&number));
+                if let Some(courier_code) = courier_code {
+                    request["courierCode"] = serde_json::json!([courier_code]);
+                }
+                chunk_tasks.push((number.clone(), tokio::spawn(async move {
                     let tracking_url = format!("{}/trackers/track", BASE_URL);
                     let response = client
                         .post(&tracking_url)
                         .header("Authorization", format!("Bearer {}", api_key))
                         .header("Content-Type", "application/json")
-                        .json(&serde_json::json!({
-                            "trackingNumber": &number,
-                            "settings": {
-                                "restrictTrackingToCourierCode": false
-                            }
-                        }))
+                        .json(&request)
                         .send()
                         .await;
 
@@ -63,16 +68,7 @@ impl TrackingClient {
                                         reqwest::StatusCode::OK | reqwest::StatusCode::CREATED => {
                                             match parse_tracking_info(&body) {
                                                 Some(info) => info,
-                                                None => TrackingInfo {
-                                                    id_ship: number.clone(),
-                                                    label: "Status unknown".to_string(),
-                                                    status: "Unknown".to_string(),
-                                                    events: Vec::new(),
-                                                    timeline: Vec::new(),
-                                                    url: None,
-                                                    has_error: true,
-                                                    error_message: Some("Failed to parse tracking data".to_string()),
-                                                },
+                                                None => TrackingInfo::unavailable(&number, "Failed to parse tracking data"),
                                             }
                                         },
                                         _ => {
@@ -85,52 +81,25 @@ impl TrackingClient {
                                             } else {
                                                 format!("API error: {}", status)
                                             };
-                                            TrackingInfo {
-                                                id_ship: number,
-                                                label: "Status unknown".to_string(),
-                                                status: "Unknown".to_string(),
-                                                events: Vec::new(),
-                                                timeline: Vec::new(),
-                                                url: None,
-                                                has_error: true,
-                                                error_message: Some(error_message),
-                                            }
+                                            TrackingInfo::unavailable(&number, &error_message)
                                         }
                                     }
                                 },
-                                Err(e) => TrackingInfo {
-                                    id_ship: number,
-                                    label: "Status unknown".to_string(),
-                                    status: "Unknown".to_string(),
-                                    events: Vec::new(),
-                                    timeline: Vec::new(),
-                                    url: None,
-                                    has_error: true,
-                                    error_message: Some(format!("Network error: {}", e)),
-                                },
+                                Err(e) => TrackingInfo::unavailable(&number, &format!("Network error: {}", e)),
                             }
                         },
-                        Err(e) => TrackingInfo {
-                            id_ship: number,
-                            label: "Status unknown".to_string(),
-                            status: "Unknown".to_string(),
-                            events: Vec::new(),
-                            timeline: Vec::new(),
-                            url: None,
-                            has_error: true,
-                            error_message: Some(format!("Network error: {}", e)),
-                        },
+                        Err(e) => TrackingInfo::unavailable(&number, &format!("Network error: {}", e)),
                     }
-                }));
+                })));
             }
 
             tasks.extend(chunk_tasks);
             tokio::time::sleep(Duration::from_millis(200)).await;
         }
 
-        for task in tasks {
+        for (number, task) in tasks {
             if let Ok(info) = task.await {
-                results.push(info);
+                results.push((number, info));
             }
         }
 
@@ -142,16 +111,7 @@ pub fn parse_tracking_info(json: &str) -> Option<TrackingInfo> {
     let api_response: ApiResponse = match serde_json::from_str(json) {
         Ok(resp) => resp,
         Err(e) => {
-            return Some(TrackingInfo {
-                id_ship: "".to_string(),
-                label: "".to_string(),
-                status: "".to_string(),
-                events: Vec::new(),
-                timeline: Vec::new(),
-                url: None,
-                has_error: true,
-                error_message: Some(e.to_string()),
-            });
+            return Some(TrackingInfo::unavailable("", &e.to_string()));
         },
     };
 
@@ -161,16 +121,10 @@ pub fn parse_tracking_info(json: &str) -> Option<TrackingInfo> {
     };
 
     if tracking.events.is_empty() {
-        return Some(TrackingInfo {
-            id_ship: tracking.tracker.tracking_number.clone(),
-            label: "No tracking data available".to_string(),
-            status: "Unknown".to_string(),
-            events: Vec::new(),
-            timeline: Vec::new(),
-            url: None,
-            has_error: true,
-            error_message: Some("No tracking data available".to_string()),
-        });
+        return Some(TrackingInfo::unavailable(
+            &tracking.tracker.tracking_number,
+            "No tracking data available",
+        ));
     }
     
     let shipment = &tracking.shipment;
@@ -231,18 +185,31 @@ pub fn parse_tracking_info(json: &str) -> Option<TrackingInfo> {
         step.status = step_milestone.is_completed_at(current_milestone_enum);
     }
 
-    let highest_milestone = tracking.events.iter()
-        .map(|e| StatusMilestone::from_str(&e.status_milestone))
-        .max_by_key(|m| m.order())
-        .unwrap_or(current_milestone_enum);
-    
-    let label = {
+    let mut info = TrackingInfo {
+        id_ship: tracking.tracker.tracking_number.clone(),
+        label: String::new(),
+        events: tracking.events.clone(),
+        timeline,
+        url: Some(format!("{}{}", SHIP24_TRACKING_URL, tracking.tracker.tracking_number)),
+        has_error: false,
+        error_message: None,
+        estimated_delivery: shipment
+            .delivery
+            .as_ref()
+            .and_then(|delivery| delivery.estimated_delivery_date.as_deref())
+            .and_then(parse_datetime),
+        shipment_milestone: Some(shipment.status_milestone.clone()).filter(|milestone| !milestone.is_empty()),
+    };
+
+    // The label describes the same milestone the status, icon and sections show.
+    let milestone = info.milestone();
+    info.label = {
         tracking.events.iter()
-            .filter(|e| StatusMilestone::from_str(&e.status_milestone) == highest_milestone)
+            .filter(|e| StatusMilestone::from_str(&e.status_milestone) == milestone)
             .next()
             .and_then(|e| e.status.clone())
             .unwrap_or_else(|| {
-                match highest_milestone {
+                match milestone {
                     StatusMilestone::Delivered => "Package has been delivered".to_string(),
                     StatusMilestone::InTransit => "Package is in transit".to_string(),
                     StatusMilestone::InfoReceived => "Package information received".to_string(),
@@ -255,16 +222,5 @@ pub fn parse_tracking_info(json: &str) -> Option<TrackingInfo> {
             })
     };
 
-    let info = TrackingInfo {
-        id_ship: tracking.tracker.tracking_number.clone(),
-        label,
-        status: highest_milestone.to_string().to_string(),
-        events: tracking.events.clone(),
-        timeline,
-        url: Some(format!("{}{}", SHIP24_TRACKING_URL, tracking.tracker.tracking_number)),
-        has_error: false,
-        error_message: None,
-    };
-
     return Some(info);
 } 
\ No newline at end of file
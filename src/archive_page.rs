@@ -0,0 +1 @@
+pub mod archive;
\ No newline at end of file
@@ -0,0 +1,219 @@
+use crate::api::models::TrackingInfo;
+use crate::details_page::details::create_details_page;
+use crate::home_page::status_badge::{create_status_icon, create_status_pill};
+use crate::package::store::PackageStore;
+use crate::package::Package;
+use crate::storage::{load_saved_data, update_package_metadata, PackageMetadata};
+use adw::{
+    glib,
+    gtk::{Align, Box, Button, ListBox, Orientation, PolicyType, ScrolledWindow, SearchEntry},
+    prelude::*,
+    ActionRow, HeaderBar, NavigationPage, NavigationView, StatusPage, Toast, ToastOverlay,
+    ToolbarView,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn matches_search(row: &ActionRow, query: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+    let query = query.to_lowercase();
+    row.title().to_lowercase().contains(&query) || row.subtitle().is_some_and(|s| s.to_lowercase().contains(&query))
+}
+
+fn create_archived_row(
+    archived_package: &Package,
+    metadata: &PackageMetadata,
+    info: TrackingInfo,
+    store: &PackageStore,
+    nav_view: &NavigationView,
+    toast_overlay: &ToastOverlay,
+    restored: &Rc<RefCell<Vec<String>>>,
+) -> ActionRow {
+    let subtitle = if info.has_error {
+        info.error_message
+            .clone()
+            .unwrap_or_else(|| "No tracking data available".to_string())
+    } else {
+        info.label.clone()
+    };
+
+    let number = archived_package.number();
+    let number = number.as_str();
+    let package = ActionRow::builder()
+        .title(metadata.title(number))
+        .subtitle(metadata.subtitle(number, &subtitle))
+        .use_markup(false)
+        .activatable(true)
+        .build();
+
+    let restore_btn = Button::builder()
+        .icon_name("edit-undo-symbolic")
+        .tooltip_markup("Move back to tracked packages")
+        .valign(Align::Center)
+        .build();
+
+    let delete_btn = Button::builder()
+        .icon_name("user-trash-symbolic")
+        .tooltip_markup("Delete this package")
+        .valign(Align::Center)
+        .build();
+
+    let package_clone = package.clone();
+    let number_clone = number.to_string();
+    let restored_clone = restored.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+    restore_btn.connect_clicked(move |_| {
+        if update_package_metadata(&number_clone, PackageMetadata::restore).is_ok() {
+            restored_clone.borrow_mut().push(number_clone.clone());
+            if let Some(list) = package_clone.parent().and_downcast::<ListBox>() {
+                list.remove(&package_clone);
+            }
+            toast_overlay_clone.add_toast(Toast::new("Package moved back to tracked packages"));
+        }
+    });
+
+    let package_clone = package.clone();
+    let number_clone = number.to_string();
+    let store_clone = store.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+    delete_btn.connect_clicked(move |_| {
+        let Some(list) = package_clone.parent().and_downcast::<ListBox>() else {
+            return;
+        };
+        let position = package_clone.index();
+        list.remove(&package_clone);
+
+        let package_clone = package_clone.clone();
+        store_clone.delete_with_undo(vec![number_clone.clone()], &toast_overlay_clone, move || {
+            list.insert(&package_clone, position);
+        });
+    });
+
+    let nav_view_clone = nav_view.clone();
+    let package_clone = archived_package.clone();
+    let store_clone = store.clone();
+    package.connect_activated(move |_| {
+        nav_view_clone.push(&create_details_page(&package_clone, &store_clone));
+    });
+
+    package.add_prefix(&create_status_icon(&info));
+    package.add_suffix(&create_status_pill(&info));
+    package.add_suffix(&restore_btn);
+    package.add_suffix(&delete_btn);
+    package
+}
+
+pub fn create_archive_page(
+    nav_view: &NavigationView,
+    toast_overlay: &ToastOverlay,
+    store: &PackageStore,
+) -> NavigationPage {
+    let nav_page = NavigationPage::builder()
+        .title("Archive")
+        .tag("archive")
+        .build();
+
+    let content = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(20)
+        .margin_bottom(20)
+        .margin_top(20)
+        .margin_start(20)
+        .margin_end(20)
+        .width_request(600)
+        .halign(Align::Center)
+        .build();
+
+    let search_entry = SearchEntry::builder()
+        .placeholder_text("Search archived packages")
+        .build();
+
+    let list = ListBox::builder()
+        .css_classes(vec!["boxed-list"])
+        .valign(Align::Start)
+        .build();
+
+    let no_result = StatusPage::builder()
+        .title("No archived packages")
+        .description("Delivered packages are archived here.")
+        .icon_name("folder-symbolic")
+        .build();
+    list.set_placeholder(Some(&no_result));
+
+    let restored = Rc::new(RefCell::new(Vec::new()));
+    let data = load_saved_data();
+    for (index, number) in data.tracking_numbers.iter().enumerate() {
+        let Some(metadata) = data.packages.get(number).filter(|meta| meta.archived) else {
+            continue;
+        };
+        if store.is_pending_deletion(number) {
+            continue;
+        }
+        let info = metadata.last_info.clone().unwrap_or_else(|| {
+            TrackingInfo::unavailable(number, "No tracking information available for this package")
+        });
+        let archived_package = Package::new(number, metadata, index as u32);
+        list.append(&create_archived_row(
+            &archived_package,
+            metadata,
+            info,
+            store,
+            nav_view,
+            toast_overlay,
+            &restored,
+        ));
+    }
+
+    let search_clone = search_entry.clone();
+    list.set_filter_func(move |row| {
+        row.downcast_ref::<ActionRow>()
+            .is_some_and(|row| matches_search(row, &search_clone.text()))
+    });
+
+    let list_clone = list.clone();
+    let no_result_clone = no_result.clone();
+    search_entry.connect_search_changed(move |entry| {
+        if entry.text().is_empty() {
+            no_result_clone.set_title("No archived packages");
+            no_result_clone.set_description(Some("Delivered packages are archived here."));
+        } else {
+            no_result_clone.set_title("No results found");
+            no_result_clone.set_description(Some("Try a different search."));
+        }
+        list_clone.invalidate_filter();
+    });
+
+    // Packages moved back are listed again on the home page and refreshed, since they were not
+    // checked while archived.
+    let store_clone = store.clone();
+    nav_page.connect_hidden(move |_| {
+        let numbers = restored.take();
+        if numbers.is_empty() {
+            return;
+        }
+        store_clone.sync_with_storage();
+        let packages: Vec<Package> = numbers.iter().filter_map(|number| store_clone.find(number)).collect();
+        let store = store_clone.clone();
+        glib::spawn_future_local(async move {
+            store.refresh(&packages).await;
+        });
+    });
+
+    content.append(&search_entry);
+    content.append(&list);
+
+    let scrolled_window = ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Never)
+        .vexpand(true)
+        .child(&content)
+        .build();
+
+    let toolbar = ToolbarView::new();
+    toolbar.add_top_bar(&HeaderBar::new());
+    toolbar.set_content(Some(&scrolled_window));
+    nav_page.set_child(Some(&toolbar));
+
+    nav_page
+}
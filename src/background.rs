@@ -0,0 +1,149 @@
+use crate::storage::{load_settings, save_settings};
+use adw::gtk::{self as gtk, gio, glib};
+use adw::prelude::*;
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const APP_ID: &str = "io.github.alrick.simple_package_tracker";
+/// Command line option starting the app without opening its window.
+pub const BACKGROUND_OPTION: &str = "background";
+
+thread_local! {
+    /// Keeps the application alive without windows while running in the background.
+    static HOLD_GUARD: RefCell<Option<gio::ApplicationHoldGuard>> = const { RefCell::new(None) };
+}
+
+fn is_sandboxed() -> bool {
+    Path::new("/.flatpak-info").exists()
+}
+
+/// Holds or releases the application, makes the windows hide instead of closing and keeps the
+/// autostart entry in line with the settings.
+pub fn apply_background_settings() {
+    let Some(app) = gio::Application::default().and_downcast::<gtk::Application>() else {
+        return;
+    };
+    let settings = load_settings();
+
+    for window in app.windows() {
+        window.set_hide_on_close(settings.run_in_background);
+    }
+    HOLD_GUARD.with_borrow_mut(|guard| match (settings.run_in_background, guard.is_some()) {
+        (true, false) => *guard = Some(app.hold()),
+        (false, true) => *guard = None,
+        _ => {}
+    });
+
+    let autostart = settings.run_in_background && settings.autostart;
+    if is_sandboxed() {
+        if settings.run_in_background {
+            request_background(&app, autostart);
+        }
+    } else if let Err(e) = update_autostart_file(autostart) {
+        eprintln!("Failed to update the autostart entry: {}", e);
+    }
+}
+
+/// Asks the Background portal to let the sandboxed app run without a window, and to start it at
+/// login when `autostart` is set. Running in the background is turned off if the user denies it.
+fn request_background(app: &gtk::Application, autostart: bool) {
+    let Some(connection) = app.dbus_connection() else {
+        return;
+    };
+    let Some(sender) = connection.unique_name() else {
+        return;
+    };
+    // The request path is known up front so the response can't arrive before the subscription.
+    let token = format!("spt{}", glib::random_int());
+    let handle = format!(
+        "/org/freedesktop/portal/desktop/request/{}/{}",
+        sender.trim_start_matches(':').replace('.', "_"),
+        token
+    );
+
+    let subscription = Rc::new(RefCell::new(None));
+    let subscription_clone = subscription.clone();
+    let id = connection.signal_subscribe(
+        Some("org.freedesktop.portal.Desktop"),
+        Some("org.freedesktop.portal.Request"),
+        Some("Response"),
+        Some(&handle),
+        None,
+        gio::DBusSignalFlags::NONE,
+        move |connection, _, _, _, _, parameters| {
+            if let Some(id) = subscription_clone.take() {
+                connection.signal_unsubscribe(id);
+            }
+            let allowed = parameters
+                .get::<(u32, glib::VariantDict)>()
+                .is_some_and(|(response, results)| {
+                    response == 0 && results.lookup::<bool>("background").ok().flatten().unwrap_or(false)
+                });
+            if !allowed {
+                eprintln!("Running in the background was not allowed");
+                let mut settings = load_settings();
+                settings.run_in_background = false;
+                let _ = save_settings(&settings);
+                apply_background_settings();
+            }
+        },
+    );
+    subscription.replace(Some(id));
+
+    let options = glib::VariantDict::new(None);
+    options.insert("handle_token", token);
+    options.insert("reason", "Check packages and send notifications while the window is closed");
+    options.insert("autostart", autostart);
+    options.insert("commandline", vec!["simple-package-tracker".to_string(), format!("--{}", BACKGROUND_OPTION)]);
+    let parameters = glib::Variant::tuple_from_iter(["".to_variant(), options.end()]);
+
+    let connection_clone = connection.clone();
+    connection.call(
+        Some("org.freedesktop.portal.Desktop"),
+        "/org/freedesktop/portal/desktop",
+        "org.freedesktop.portal.Background",
+        "RequestBackground",
+        Some(&parameters),
+        Some(glib::VariantTy::new("(o)").expect("Valid variant type")),
+        gio::DBusCallFlags::NONE,
+        -1,
+        gio::Cancellable::NONE,
+        move |result| {
+            if let Err(e) = result {
+                eprintln!("Failed to request running in the background: {}", e);
+                if let Some(id) = subscription.take() {
+                    connection_clone.signal_unsubscribe(id);
+                }
+            }
+        },
+    );
+}
+
+fn autostart_file() -> PathBuf {
+    glib::user_config_dir().join("autostart").join(format!("{}.desktop", APP_ID))
+}
+
+/// Outside Flatpak, starting at login is a desktop entry in the user's autostart directory.
+fn update_autostart_file(enabled: bool) -> std::io::Result<()> {
+    let path = autostart_file();
+    if !enabled {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+
+    let exec = std::env::current_exe()?;
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=Simple Package Tracker\nIcon={}\nExec=\"{}\" --{}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+        APP_ID,
+        exec.display(),
+        BACKGROUND_OPTION
+    );
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, entry)
+}
@@ -0,0 +1,128 @@
+#[cfg(feature = "barcode")]
+mod decode;
+
+use crate::tracking_number::extract::{extract_tracking_numbers, Candidate};
+use crate::tracking_number::normalize::is_url;
+use crate::tracking_number::url::parse_tracking_url;
+use crate::tracking_number::detect_carrier;
+use std::path::Path;
+use thiserror::Error;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "gif", "bmp", "tif", "tiff"];
+
+/// Shipping labels often wrap the tracking number in routing data, e.g. USPS prefixes the ZIP
+/// code ("420" + ZIP) and FedEx Ground adds a service code; these are the lengths to try from the end.
+const TRAILING_NUMBER_LENGTHS: &[usize] = &[22, 20, 15, 12];
+
+#[derive(Debug, Error)]
+pub enum BarcodeError {
+    #[error("Could not read {0}")]
+    #[cfg_attr(not(feature = "barcode"), allow(dead_code))]
+    Load(String),
+    #[error("Barcode scanning is not available in this build")]
+    #[cfg_attr(feature = "barcode", allow(dead_code))]
+    Unsupported,
+}
+
+pub fn is_barcode_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase)
+        .is_some_and(|extension| extension == "pdf" || IMAGE_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// Decodes the Code 128, QR and Data Matrix barcodes of an image or PDF, rendering PDF pages first.
+#[cfg(feature = "barcode")]
+pub fn decode_file(path: &Path) -> Result<Vec<String>, BarcodeError> {
+    decode::decode_file(path)
+}
+
+#[cfg(not(feature = "barcode"))]
+pub fn decode_file(_path: &Path) -> Result<Vec<String>, BarcodeError> {
+    Err(BarcodeError::Unsupported)
+}
+
+fn trailing_number(text: &str) -> Option<Candidate> {
+    let digits: String = text.chars().filter(|c| !c.is_control()).collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    TRAILING_NUMBER_LENGTHS
+        .iter()
+        .filter(|&&length| digits.len() > length)
+        .find_map(|&length| {
+            let number = &digits[digits.len() - length..];
+            detect_carrier(number).map(|carrier| Candidate { number: number.to_string(), carrier })
+        })
+}
+
+/// Tracking numbers held by decoded barcodes, either plainly, inside label routing data or as a tracking link.
+pub fn tracking_candidates(texts: &[String]) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for text in texts {
+        let found = if is_url(text.trim()) {
+            parse_tracking_url(text)
+                .and_then(|url| Some(Candidate { carrier: url.carrier?, number: url.number }))
+                .into_iter()
+                .collect()
+        } else {
+            match extract_tracking_numbers(text) {
+                found if found.is_empty() => trailing_number(text).into_iter().collect(),
+                found => found,
+            }
+        };
+        for candidate in found {
+            if !candidates.iter().any(|c| c.number == candidate.number) {
+                candidates.push(candidate);
+            }
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracking_number::Carrier;
+
+    fn numbers(texts: &[&str]) -> Vec<(String, Carrier)> {
+        let texts: Vec<String> = texts.iter().map(|text| text.to_string()).collect();
+        tracking_candidates(&texts)
+            .into_iter()
+            .map(|candidate| (candidate.number, candidate.carrier))
+            .collect()
+    }
+
+    #[test]
+    fn routing_data_is_stripped() {
+        let usps = trailing_number("420100019400111899223197428497").map(|c| (c.number, c.carrier));
+        assert_eq!(usps, Some(("9400111899223197428497".to_string(), Carrier::Usps)));
+        let fedex = trailing_number("\u{1d}96123456789012").map(|c| (c.number, c.carrier));
+        assert_eq!(fedex, Some(("123456789012".to_string(), Carrier::FedEx)));
+    }
+
+    #[test]
+    fn numbers_that_are_not_tracking_numbers_are_left_out() {
+        assert!(trailing_number("9400111899223197428497").is_none());
+        assert!(trailing_number("4201000194001118992231974284AB").is_none());
+        assert!(trailing_number("12345").is_none());
+    }
+
+    #[test]
+    fn candidates_from_plain_text_routing_data_and_links() {
+        assert_eq!(
+            numbers(&[
+                "1Z999AA10123456784",
+                "420100019400111899223197428497",
+                "https://www.ups.com/track?tracknum=1Z999AA10123456784",
+                "https://example.com/not-a-tracking-link",
+                "RR123456785GB",
+            ]),
+            vec![
+                ("1Z999AA10123456784".to_string(), Carrier::Ups),
+                ("9400111899223197428497".to_string(), Carrier::Usps),
+                ("RR123456785GB".to_string(), Carrier::RoyalMail),
+            ]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use super::BarcodeError;
+use adw::{
+    gio::{self, prelude::*},
+    gtk::{cairo, gdk_pixbuf::Pixbuf},
+};
+use rxing::BarcodeFormat;
+use std::path::Path;
+
+/// PDF pages are sized in points, render them larger so thin label bars stay readable.
+const PDF_RENDER_SCALE: f64 = 4.0;
+
+const FORMATS: &[BarcodeFormat] = &[
+    BarcodeFormat::CODE_128,
+    BarcodeFormat::QR_CODE,
+    BarcodeFormat::DATA_MATRIX,
+];
+
+struct LumaImage {
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+fn luma(red: u8, green: u8, blue: u8) -> u8 {
+    ((u32::from(red) * 299 + u32::from(green) * 587 + u32::from(blue) * 114) / 1000) as u8
+}
+
+fn pixbuf_to_luma(pixbuf: &Pixbuf) -> LumaImage {
+    let width = pixbuf.width() as usize;
+    let height = pixbuf.height() as usize;
+    let rowstride = pixbuf.rowstride() as usize;
+    let channels = pixbuf.n_channels() as usize;
+    let bytes = pixbuf.read_pixel_bytes();
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = &bytes[y * rowstride + x * channels..];
+            // Transparent areas of label exports are meant to be paper, not ink.
+            if channels == 4 && pixel[3] < 128 {
+                pixels.push(u8::MAX);
+            } else {
+                pixels.push(luma(pixel[0], pixel[1], pixel[2]));
+            }
+        }
+    }
+    LumaImage { pixels, width: width as u32, height: height as u32 }
+}
+
+fn load_error(path: &Path) -> BarcodeError {
+    BarcodeError::Load(path.display().to_string())
+}
+
+fn render_pdf(path: &Path) -> Result<Vec<LumaImage>, BarcodeError> {
+    let uri = gio::File::for_path(path).uri();
+    let document = poppler::Document::from_file(&uri, None).map_err(|_| load_error(path))?;
+
+    let mut pages = Vec::new();
+    for index in 0..document.n_pages() {
+        let Some(page) = document.page(index) else {
+            continue;
+        };
+        let (page_width, page_height) = page.size();
+        let width = (page_width * PDF_RENDER_SCALE).ceil() as i32;
+        let height = (page_height * PDF_RENDER_SCALE).ceil() as i32;
+
+        let mut surface = cairo::ImageSurface::create(cairo::Format::Rgb24, width, height)
+            .map_err(|_| load_error(path))?;
+        {
+            let context = cairo::Context::new(&surface).map_err(|_| load_error(path))?;
+            context.set_source_rgb(1.0, 1.0, 1.0);
+            context.paint().map_err(|_| load_error(path))?;
+            context.scale(PDF_RENDER_SCALE, PDF_RENDER_SCALE);
+            page.render(&context);
+        }
+        surface.flush();
+
+        let stride = surface.stride() as usize;
+        let data = surface.data().map_err(|_| load_error(path))?;
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                // Rgb24 stores native endian 0xXXRRGGBB words.
+                let word = u32::from_ne_bytes(data[y * stride + x * 4..][..4].try_into().unwrap());
+                pixels.push(luma((word >> 16) as u8, (word >> 8) as u8, word as u8));
+            }
+        }
+        pages.push(LumaImage { pixels, width: width as u32, height: height as u32 });
+    }
+    Ok(pages)
+}
+
+fn decode_image(image: LumaImage) -> Vec<String> {
+    rxing::helpers::detect_multiple_in_luma(image.pixels, image.width, image.height)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|result| FORMATS.contains(result.getBarcodeFormat()))
+        .map(|result| result.getText().to_string())
+        .collect()
+}
+
+pub fn decode_file(path: &Path) -> Result<Vec<String>, BarcodeError> {
+    let is_pdf = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+    let images = if is_pdf {
+        render_pdf(path)?
+    } else {
+        let pixbuf = Pixbuf::from_file(path).map_err(|_| load_error(path))?;
+        vec![pixbuf_to_luma(&pixbuf)]
+    };
+
+    let mut texts: Vec<String> = Vec::new();
+    for text in images.into_iter().flat_map(decode_image) {
+        if !texts.contains(&text) {
+            texts.push(text);
+        }
+    }
+    Ok(texts)
+}
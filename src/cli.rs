@@ -0,0 +1,326 @@
+use crate::api::models::TrackingInfo;
+use crate::api::tracking::TrackingClient;
+use crate::storage::{load_saved_data, migrate_saved_data, save_saved_data, PackageMetadata};
+use crate::tracking_number::normalize::{is_url, normalize};
+use crate::tracking_number::url::parse_tracking_url;
+use crate::tracking_number::validate::{validate, Validation};
+use adw::glib::ExitCode;
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+
+const USAGE: &str = "Usage:
+  simple-package-tracker list [--json]
+  simple-package-tracker add <number>... [--json]
+  simple-package-tracker remove <number>... [--json]
+  simple-package-tracker status [number] [--json]";
+
+/// Commands run from a terminal or a script, without opening the window.
+enum Command {
+    List,
+    Add(Vec<String>),
+    Remove(Vec<String>),
+    Status(Option<String>),
+}
+
+/// A package as printed by the commands, one table row or JSON object.
+#[derive(Serialize)]
+struct PackageSummary {
+    number: String,
+    nickname: Option<String>,
+    status: Option<String>,
+    details: Option<String>,
+    last_event: Option<DateTime<Utc>>,
+    estimated_delivery: Option<DateTime<Utc>>,
+    archived: bool,
+    error: Option<String>,
+}
+
+impl PackageSummary {
+    /// `info` is the latest result, which may be an error while `metadata` keeps the last good one.
+    fn new(number: &str, metadata: &PackageMetadata, info: Option<&TrackingInfo>) -> Self {
+        let info = info.or(metadata.last_info.as_ref());
+        let error = info.filter(|info| info.has_error).map(|info| {
+            info.error_message.clone().unwrap_or_else(|| "Tracking failed".to_string())
+        });
+        let known = info.filter(|info| !info.has_error).or(metadata.last_info.as_ref());
+        Self {
+            number: number.to_string(),
+            nickname: metadata.nickname.clone(),
+            status: known.map(|info| info.status().to_string()),
+            details: known.map(|info| info.label.clone()),
+            last_event: known.and_then(|info| info.latest_event()).map(|event| event.occurrence_datetime),
+            estimated_delivery: known.and_then(|info| info.estimated_delivery),
+            archived: metadata.archived,
+            error,
+        }
+    }
+
+    fn columns(&self) -> [String; 5] {
+        let mut status = self.status.clone().unwrap_or_else(|| "Not checked yet".to_string());
+        if self.archived {
+            status.push_str(" (archived)");
+        }
+        let details = match &self.error {
+            Some(error) => format!("Error: {}", error),
+            None => self.details.clone().unwrap_or_default(),
+        };
+        [
+            self.number.clone(),
+            self.nickname.clone().unwrap_or_default(),
+            status,
+            self.last_event
+                .map(|date| date.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+            details,
+        ]
+    }
+}
+
+/// Reads a command from the arguments, `None` when they are meant for the application itself.
+fn parse_args(args: &[String]) -> Option<Result<(Command, bool), String>> {
+    let name = args.get(1)?;
+    if !matches!(name.as_str(), "list" | "add" | "remove" | "status") {
+        return None;
+    }
+
+    let mut json = false;
+    let mut numbers = Vec::new();
+    for arg in &args[2..] {
+        match arg.as_str() {
+            "--json" => json = true,
+            option if option.starts_with('-') => return Some(Err(format!("Unknown option: {}", option))),
+            number => numbers.push(number.to_string()),
+        }
+    }
+
+    let command = match name.as_str() {
+        "list" if numbers.is_empty() => Command::List,
+        "add" if !numbers.is_empty() => Command::Add(numbers),
+        "remove" if !numbers.is_empty() => Command::Remove(numbers),
+        "status" if numbers.len() <= 1 => Command::Status(numbers.pop()),
+        _ => return Some(Err(format!("Wrong arguments for {}", name))),
+    };
+    Some(Ok((command, json)))
+}
+
+/// Runs the command given in `args`, returning `None` to start the application instead.
+pub async fn run(args: &[String]) -> Option<ExitCode> {
+    let (command, json) = match parse_args(args)? {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            return Some(ExitCode::from(2));
+        }
+    };
+
+    migrate_saved_data();
+    let result = match command {
+        Command::List => Ok(list()),
+        Command::Add(inputs) => add(&inputs),
+        Command::Remove(inputs) => remove(&inputs),
+        Command::Status(input) => status(input.as_deref()).await,
+    };
+
+    Some(match result {
+        Ok(summaries) => {
+            print_summaries(&summaries, json);
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    })
+}
+
+/// The tracking number typed or pasted as a link, with the Ship24 courier code the link gives.
+fn read_number(input: &str) -> Result<(String, Option<&'static str>), String> {
+    if is_url(input.trim()) {
+        return parse_tracking_url(input)
+            .map(|url| {
+                let courier_code = url.carrier.and_then(|carrier| carrier.ship24_code(&url.number));
+                (url.number, courier_code)
+            })
+            .ok_or_else(|| format!("Not a recognized tracking link: {}", input));
+    }
+    let number = normalize(input);
+    if number.is_empty() {
+        return Err(format!("Not a tracking number: {}", input));
+    }
+    if let Validation::Invalid(carrier) = validate(&number) {
+        return Err(format!("Not a valid {} tracking number: {}", carrier.name(), input));
+    }
+    Ok((number, None))
+}
+
+fn list() -> Vec<PackageSummary> {
+    let data = load_saved_data();
+    data.tracking_numbers
+        .iter()
+        .map(|number| {
+            let metadata = data.packages.get(number).cloned().unwrap_or_default();
+            PackageSummary::new(number, &metadata, None)
+        })
+        .collect()
+}
+
+/// Tracks the numbers, moving the archived ones back to the tracked packages.
+fn add(inputs: &[String]) -> Result<Vec<PackageSummary>, String> {
+    let numbers = inputs
+        .iter()
+        .map(|input| read_number(input))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut data = load_saved_data();
+    for (number, courier_code) in &numbers {
+        if !data.tracking_numbers.contains(number) {
+            data.tracking_numbers.push(number.clone());
+        }
+        let meta = data.packages.entry(number.clone()).or_default();
+        meta.restore();
+        if let Some(courier_code) = courier_code {
+            meta.courier_code = Some(courier_code.to_string());
+        }
+    }
+    save_saved_data(&data).map_err(|e| format!("Failed to save packages: {}", e))?;
+
+    Ok(numbers
+        .iter()
+        .map(|(number, _)| PackageSummary::new(number, &data.packages[number], None))
+        .collect())
+}
+
+fn remove(inputs: &[String]) -> Result<Vec<PackageSummary>, String> {
+    let mut data = load_saved_data();
+    let mut removed = Vec::new();
+    for input in inputs {
+        let (number, _) = read_number(input)?;
+        if !data.tracking_numbers.contains(&number) {
+            return Err(format!("Not a tracked package: {}", number));
+        }
+        let metadata = data.packages.remove(&number).unwrap_or_default();
+        data.tracking_numbers.retain(|tracked| *tracked != number);
+        removed.push(PackageSummary::new(&number, &metadata, None));
+    }
+    save_saved_data(&data).map_err(|e| format!("Failed to save packages: {}", e))?;
+    Ok(removed)
+}
+
+/// Fetches fresh tracking info for `input`, or for every package that isn't archived, saving it
+/// for the tracked ones.
+async fn status(input: Option<&str>) -> Result<Vec<PackageSummary>, String> {
+    let mut data = load_saved_data();
+    let numbers: Vec<(String, Option<String>)> = match input {
+        Some(input) => {
+            let (number, courier_code) = read_number(input)?;
+            let courier_code = courier_code
+                .map(str::to_string)
+                .or_else(|| data.packages.get(&number).and_then(|meta| meta.courier_code.clone()));
+            vec![(number, courier_code)]
+        }
+        None => data
+            .tracking_numbers
+            .iter()
+            .filter(|number| !data.packages.get(*number).is_some_and(|meta| meta.archived))
+            .map(|number| {
+                let courier_code = data.packages.get(number).and_then(|meta| meta.courier_code.clone());
+                (number.clone(), courier_code)
+            })
+            .collect(),
+    };
+    if numbers.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let results = TrackingClient::new().track_numbers(&numbers).await;
+
+    data = load_saved_data();
+    data.quota.record(numbers.len() as u32);
+    let mut summaries = Vec::with_capacity(results.len());
+    for (number, info) in results {
+        let summary = if data.tracking_numbers.contains(&number) {
+            let meta = data.packages.entry(number.clone()).or_default();
+            meta.record_check(&info);
+            PackageSummary::new(&number, meta, Some(&info))
+        } else {
+            PackageSummary::new(&number, &PackageMetadata::default(), Some(&info))
+        };
+        summaries.push(summary);
+    }
+    save_saved_data(&data).map_err(|e| format!("Failed to save packages: {}", e))?;
+    Ok(summaries)
+}
+
+fn print_summaries(summaries: &[PackageSummary], json: bool) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(summaries).unwrap_or_default());
+        return;
+    }
+
+    let header = ["NUMBER", "NAME", "STATUS", "LAST EVENT", "DETAILS"].map(str::to_string);
+    let rows: Vec<[String; 5]> = std::iter::once(header)
+        .chain(summaries.iter().map(PackageSummary::columns))
+        .collect();
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.chars().count());
+        }
+    }
+    for row in &rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(column, width)| format!("{:width$}", column, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Option<Result<(Command, bool), String>> {
+        let args: Vec<String> = std::iter::once("simple-package-tracker")
+            .chain(args.iter().copied())
+            .map(str::to_string)
+            .collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn application_arguments_are_left_alone() {
+        assert!(parse(&[]).is_none());
+        assert!(parse(&["--background"]).is_none());
+        assert!(parse(&["package-tracker://show/RR123456785GB"]).is_none());
+    }
+
+    #[test]
+    fn commands() {
+        assert!(matches!(parse(&["list"]), Some(Ok((Command::List, false)))));
+        assert!(matches!(parse(&["list", "--json"]), Some(Ok((Command::List, true)))));
+        assert!(matches!(
+            parse(&["add", "RR123456785GB", "1Z999AA10123456784"]),
+            Some(Ok((Command::Add(numbers), false))) if numbers == ["RR123456785GB", "1Z999AA10123456784"]
+        ));
+        assert!(matches!(
+            parse(&["remove", "--json", "RR123456785GB"]),
+            Some(Ok((Command::Remove(numbers), true))) if numbers == ["RR123456785GB"]
+        ));
+        assert!(matches!(parse(&["status"]), Some(Ok((Command::Status(None), false)))));
+        assert!(matches!(
+            parse(&["status", "RR123456785GB"]),
+            Some(Ok((Command::Status(Some(number)), false))) if number == "RR123456785GB"
+        ));
+    }
+
+    #[test]
+    fn usage_errors() {
+        assert_eq!(parse(&["list", "-v"]).and_then(Result::err), Some("Unknown option: -v".to_string()));
+        assert!(matches!(parse(&["list", "RR123456785GB"]), Some(Err(_))));
+        assert!(matches!(parse(&["add"]), Some(Err(_))));
+        assert!(matches!(parse(&["remove"]), Some(Err(_))));
+        assert!(matches!(parse(&["status", "RR123456785GB", "1Z999AA10123456784"]), Some(Err(_))));
+    }
+}
@@ -1,16 +1,22 @@
-use crate::api::tracking::TrackingClient;
 use crate::api::models::TrackingInfo;
+use crate::package::store::PackageStore;
+use crate::package::Package;
 use adw::{
     gio::prelude::*,
     glib,
     gtk::{
-        Align, Box, Button, Image, Label, ListBox, Orientation, PolicyType, ProgressBar,
-        ScrolledWindow, Separator,
+        self, Align, Box, Button, Frame, Image, Label, ListBox, Orientation, PolicyType,
+        ProgressBar, ScrolledWindow, SelectionMode, Separator, TextBuffer, TextView, WrapMode,
     },
     prelude::*,
-    ActionRow, HeaderBar, NavigationPage, Spinner, ToolbarView,
+    ActionRow, EntryRow, HeaderBar, NavigationPage, Spinner, ToolbarView,
 };
 use chrono::Local;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+const NOTES_SAVE_DELAY: Duration = Duration::from_millis(500);
 
 pub fn create_events_history(info: &TrackingInfo, events_box: Box) -> Box {
     let events_label = Label::builder()
@@ -46,61 +52,107 @@ pub fn create_events_history(info: &TrackingInfo, events_box: Box) -> Box {
     return events_box;
 }
 
-fn show_loading_state(info: &TrackingInfo) -> ScrolledWindow {
-    let details = Box::builder()
+fn create_loading_state() -> Spinner {
+    Spinner::builder()
+        .width_request(64)
+        .height_request(64)
+        .build()
+}
+
+/// The nickname and notes editors, along with a function saving notes still waiting for typing
+/// to pause.
+fn create_notes_section(package: &Package) -> (Box, impl Fn()) {
+    let notes_box = Box::builder()
         .orientation(Orientation::Vertical)
-        .halign(Align::Center)
-        .valign(Align::Center)
-        .margin_bottom(20)
-        .margin_top(20)
-        .margin_start(20)
-        .margin_end(20)
-        .spacing(20)
+        .spacing(10)
+        .width_request(600)
         .build();
 
-    let title = Label::builder()
-        .label(&info.id_ship)
-        .css_classes(vec!["title-1"])
-        .margin_bottom(50)
+    let nickname_list = ListBox::builder()
+        .css_classes(vec!["boxed-list"])
+        .selection_mode(SelectionMode::None)
         .build();
 
-    let spinner = Spinner::builder()
-        .width_request(64)
-        .height_request(64)
+    let nickname_row = EntryRow::builder()
+        .title("Nickname")
+        .text(package.nickname().unwrap_or_default())
+        .show_apply_button(true)
         .build();
 
-    details.append(&title);
-    details.append(&spinner);
+    let package_clone = package.clone();
+    nickname_row.connect_apply(move |row| {
+        let nickname = row.text().trim().to_string();
+        package_clone.save_nickname((!nickname.is_empty()).then_some(nickname));
+    });
+    nickname_list.append(&nickname_row);
 
-    let scrolled_window = ScrolledWindow::builder()
-        .hscrollbar_policy(PolicyType::Never)
-        .vexpand(true)
-        .child(&details)
+    let notes_label = Label::builder()
+        .label("Notes")
+        .css_classes(vec!["title-3"])
+        .halign(Align::Start)
+        .margin_top(10)
         .build();
 
-    return scrolled_window;
+    let notes_view = TextView::builder()
+        .bottom_margin(12)
+        .left_margin(12)
+        .right_margin(12)
+        .top_margin(12)
+        .wrap_mode(WrapMode::WordChar)
+        .accepts_tab(false)
+        .height_request(100)
+        .build();
+    notes_view.buffer().set_text(&package.notes());
+
+    // Notes are saved once typing pauses rather than on every keystroke.
+    let pending_save: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    let pending_save_clone = pending_save.clone();
+    let package_clone = package.clone();
+    notes_view.buffer().connect_changed(move |buffer| {
+        if let Some(source) = pending_save_clone.take() {
+            source.remove();
+        }
+        let buffer = buffer.clone();
+        let package = package_clone.clone();
+        let pending_save = pending_save_clone.clone();
+        let source = glib::timeout_add_local_once(NOTES_SAVE_DELAY, move || {
+            pending_save.take();
+            save_notes(&package, &buffer);
+        });
+        pending_save_clone.replace(Some(source));
+    });
+
+    let buffer = notes_view.buffer();
+    let package = package.clone();
+    let flush = move || {
+        if let Some(source) = pending_save.take() {
+            source.remove();
+            save_notes(&package, &buffer);
+        }
+    };
+
+    let notes_frame = Frame::builder().child(&notes_view).build();
+
+    notes_box.append(&nickname_list);
+    notes_box.append(&notes_label);
+    notes_box.append(&notes_frame);
+    (notes_box, flush)
+}
+
+fn save_notes(package: &Package, buffer: &TextBuffer) {
+    let notes = buffer
+        .text(&buffer.start_iter(), &buffer.end_iter(), false)
+        .to_string();
+    package.save_notes(&notes);
 }
 
-fn create_details_content(info: &TrackingInfo) -> ScrolledWindow {
+/// Status, progress and history of `info`, the part of the page replaced after each refresh.
+fn create_tracking_section(info: &TrackingInfo) -> Box {
     let details = Box::builder()
         .orientation(Orientation::Vertical)
-        .halign(Align::Center)
-        .valign(Align::Center)
-        .margin_bottom(20)
-        .margin_top(20)
-        .margin_start(20)
-        .margin_end(20)
         .spacing(20)
         .build();
 
-    let title = Label::builder()
-        .label(&info.id_ship)
-        .css_classes(vec!["title-1"])
-        .margin_bottom(50)
-        .build();
-
-    details.append(&title);
-
     if info.has_error || info.events.is_empty() {
         let error_message = if let Some(msg) = &info.error_message {
             msg.clone()
@@ -154,7 +206,7 @@ fn create_details_content(info: &TrackingInfo) -> ScrolledWindow {
                 .build();
 
             let product_label = Label::builder()
-                .label(&format!("Status: {}", info.status))
+                .label(&format!("Status: {}", info.status()))
                 .halign(Align::End)
                 .hexpand(true)
                 .build();
@@ -169,52 +221,34 @@ fn create_details_content(info: &TrackingInfo) -> ScrolledWindow {
         details.append(&events_box);
     }
 
-    let scrolled_window = ScrolledWindow::builder()
-        .hscrollbar_policy(PolicyType::Never)
-        .vexpand(true)
-        .child(&details)
-        .build();
-
-    return scrolled_window;
+    details
 }
 
-fn create_header(info: TrackingInfo, nav_page: &NavigationPage) -> HeaderBar {
+/// The header, with a button opening the carrier's tracking page that `update_url_button` shows
+/// once the package has one.
+fn create_header(package: &Package, store: &PackageStore) -> (HeaderBar, Button) {
     let header = HeaderBar::new();
-    let info_id = info.id_ship.clone();
-    let nav_page_clone = nav_page.clone();
-    let infos = info.clone();
     let refresh_button = Button::builder()
         .icon_name("view-refresh-symbolic")
         .tooltip_markup("Refresh tracking information")
         .build();
+    package
+        .bind_property("loading", &refresh_button, "sensitive")
+        .invert_boolean()
+        .sync_create()
+        .build();
 
-    refresh_button.connect_clicked(move |button| {
-        let nav_page_clone = nav_page_clone.clone();
-        let info_id_clone = info_id.clone();
-        let infos_clone = infos.clone();
-
-        if let Some(toolbar) = nav_page_clone.child().and_downcast::<ToolbarView>() {
-            let loading_view = show_loading_state(&infos_clone);
-            toolbar.set_content(Some(&loading_view));
-            button.set_sensitive(false);
-        }
-
+    let package_clone = package.clone();
+    let store_clone = store.clone();
+    refresh_button.connect_clicked(move |_| {
+        let package = package_clone.clone();
+        let store = store_clone.clone();
         glib::spawn_future_local(async move {
-            let client = TrackingClient::new();
-            let tracking_info = client.process_tracking_numbers(&info_id_clone).await;
-            if let Some(new_info) = tracking_info.into_iter().next() {
-                let toolbar = ToolbarView::new();
-                let new_header = create_header(new_info.clone(), &nav_page_clone);
-                let details = create_details_content(&new_info);
-
-                toolbar.set_content(Some(&details));
-                toolbar.add_top_bar(&new_header);
-                nav_page_clone.set_child(Some(&toolbar));
-            }
+            store.refresh(&[package]).await;
         });
     });
 
-    if let Some(url) = info.url {
+    let url_button = {
         let style_manager = adw::StyleManager::default();
         let image = Image::builder()
             .tooltip_markup("Open tracking page in browser")
@@ -246,33 +280,138 @@ fn create_header(info: TrackingInfo, nav_page: &NavigationPage) -> HeaderBar {
             .tooltip_markup("Open tracking page in browser")
             .build();
 
-        let url_clone = url.clone();
+        let package_clone = package.clone();
         url_button.connect_clicked(move |_| {
-            if let Err(e) = open::that(&url_clone) {
+            let Some(url) = package_clone.info().and_then(|info| info.url) else {
+                return;
+            };
+            if let Err(e) = open::that(&url) {
                 eprintln!("Failed to open URL: {}", e);
             }
         });
+        url_button
+    };
+    update_url_button(&url_button, package);
 
-        header.pack_end(&url_button);
-    }
-
+    header.pack_end(&url_button);
     header.pack_start(&refresh_button);
-    return header;
+    (header, url_button)
 }
 
-pub fn create_details_page(info: &TrackingInfo) -> NavigationPage {
-    let nav_page = NavigationPage::builder()
-        .title("Package Details")
-        .tag(&info.id_ship)
+fn update_url_button(url_button: &Button, package: &Package) {
+    url_button.set_visible(package.info().is_some_and(|info| info.url.is_some()));
+}
+
+fn update_tracking_section(tracking_box: &Box, package: &Package) {
+    let info = package.info().unwrap_or_else(|| {
+        TrackingInfo::unavailable(
+            &package.number(),
+            "No tracking information available for this package",
+        )
+    });
+    replace_tracking_section(tracking_box, &create_tracking_section(&info));
+}
+
+fn replace_tracking_section(tracking_box: &Box, child: &impl IsA<gtk::Widget>) {
+    while let Some(old) = tracking_box.first_child() {
+        tracking_box.remove(&old);
+    }
+    tracking_box.append(child);
+}
+
+/// Details of `package`, following it while it is refreshed from here or from the home list.
+/// Only the tracking section is rebuilt on refresh, so edits to the nickname and notes survive.
+pub fn create_details_page(package: &Package, store: &PackageStore) -> NavigationPage {
+    let details = Box::builder()
+        .orientation(Orientation::Vertical)
+        .halign(Align::Center)
+        .valign(Align::Center)
+        .margin_bottom(20)
+        .margin_top(20)
+        .margin_start(20)
+        .margin_end(20)
+        .spacing(20)
         .build();
 
-    let toolbar = ToolbarView::new();
-    let header = create_header(info.clone(), &nav_page);
-    let details = create_details_content(info);
+    let title = Label::builder()
+        .css_classes(vec!["title-1"])
+        .selectable(true)
+        .wrap(true)
+        .build();
+    package
+        .bind_property("title", &title, "label")
+        .sync_create()
+        .build();
 
-    toolbar.set_content(Some(&details));
+    let number_label = Label::builder()
+        .label(package.number())
+        .css_classes(vec!["dim-label"])
+        .selectable(true)
+        .margin_bottom(30)
+        .build();
+
+    let (notes_section, flush_notes) = create_notes_section(package);
+    let flush_notes = Rc::new(flush_notes);
+
+    let tracking_box = Box::builder()
+        .orientation(Orientation::Vertical)
+        .build();
+    if package.loading() {
+        replace_tracking_section(&tracking_box, &create_loading_state());
+    } else {
+        update_tracking_section(&tracking_box, package);
+    }
+
+    details.append(&title);
+    details.append(&number_label);
+    details.append(&notes_section);
+    details.append(&tracking_box);
+
+    let scrolled_window = ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Never)
+        .vexpand(true)
+        .child(&details)
+        .build();
+
+    let (header, url_button) = create_header(package, store);
+    let toolbar = ToolbarView::new();
+    toolbar.set_content(Some(&scrolled_window));
     toolbar.add_top_bar(&header);
-    nav_page.set_child(Some(&toolbar));
+
+    let nav_page = NavigationPage::builder()
+        .title("Package Details")
+        .tag(package.number())
+        .child(&toolbar)
+        .build();
+    package.mark_seen();
+
+    // A refresh reloads the metadata from storage, so notes still being typed are saved first.
+    let flush_notes_clone = flush_notes.clone();
+    let tracking_box_weak = tracking_box.downgrade();
+    let url_button_weak = url_button.downgrade();
+    let handler = package.connect_loading_notify(move |package| {
+        let (Some(tracking_box), Some(url_button)) =
+            (tracking_box_weak.upgrade(), url_button_weak.upgrade())
+        else {
+            return;
+        };
+        if package.loading() {
+            flush_notes_clone();
+            replace_tracking_section(&tracking_box, &create_loading_state());
+        } else {
+            update_tracking_section(&tracking_box, package);
+            update_url_button(&url_button, package);
+        }
+    });
+
+    let handler = RefCell::new(Some(handler));
+    let package_clone = package.clone();
+    nav_page.connect_destroy(move |_| {
+        flush_notes();
+        if let Some(handler) = handler.take() {
+            package_clone.disconnect(handler);
+        }
+    });
 
     nav_page
 }
@@ -0,0 +1,255 @@
+pub mod imap;
+pub mod keyring;
+pub mod mime;
+
+use crate::storage::MailboxSettings;
+use crate::tracking_number::extract::{extract_tracking_numbers, Candidate};
+use crate::tracking_number::normalize::is_url;
+use crate::tracking_number::url::parse_tracking_url;
+use adw::gio;
+use chrono::{Duration, Local};
+use imap::ImapClient;
+use keyring::load_password;
+use mime::{parse_message, Message};
+use std::error::Error;
+
+/// How far back the watcher looks, so enabling it does not import a whole mailbox history.
+const MAILBOX_LOOKBACK_DAYS: i64 = 14;
+
+/// Words that may sit between "order" and the order number itself, e.g. "Order No.: 123-456".
+const ORDER_FILLERS: &[&str] = &["#", ":", "number", "no", "no.", "id", "nr", "nr."];
+
+/// A tracking number found in an email, with the hints used to describe the package.
+pub struct EmailPackage {
+    pub candidate: Candidate,
+    pub subject: Option<String>,
+    pub merchant: Option<String>,
+    pub order: Option<String>,
+}
+
+impl EmailPackage {
+    /// Merchant and order hints, written as package notes.
+    pub fn notes(&self) -> String {
+        let mut notes = Vec::new();
+        if let Some(merchant) = &self.merchant {
+            notes.push(format!("Merchant: {}", merchant));
+        }
+        if let Some(order) = &self.order {
+            notes.push(format!("Order: {}", order));
+        }
+        notes.join("\n")
+    }
+
+    /// Nickname naming the sender along with the subject, for packages added from a mailbox.
+    pub fn sender_nickname(&self) -> Option<String> {
+        match (&self.merchant, &self.subject) {
+            (Some(merchant), Some(subject)) => Some(format!("{}: {}", merchant, subject)),
+            (merchant, subject) => subject.clone().or_else(|| merchant.clone()),
+        }
+    }
+}
+
+pub fn is_mbox(raw: &[u8]) -> bool {
+    raw.starts_with(b"From ")
+}
+
+/// Splits an mbox file on its "From " separator lines, undoing the ">From " escaping.
+fn split_mbox(raw: &[u8]) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+    let mut current: Option<Vec<u8>> = None;
+
+    for line in raw.split_inclusive(|&b| b == b'\n') {
+        if line.starts_with(b"From ") {
+            messages.extend(current.replace(Vec::new()));
+            continue;
+        }
+        let Some(message) = current.as_mut() else {
+            continue;
+        };
+        let unescaped = line.iter().position(|&b| b != b'>').is_some_and(|quotes| {
+            quotes > 0 && line[quotes..].starts_with(b"From ")
+        });
+        message.extend_from_slice(if unescaped { &line[1..] } else { line });
+    }
+    messages.extend(current);
+    messages
+}
+
+/// Reads the messages of a single .eml file or of an mbox.
+pub fn read_messages(raw: &[u8]) -> Vec<Message> {
+    if is_mbox(raw) {
+        split_mbox(raw).iter().map(|message| parse_message(message)).collect()
+    } else {
+        vec![parse_message(raw)]
+    }
+}
+
+/// The sender's display name, else the domain of their address, as the merchant hint.
+fn merchant(from: &str) -> Option<String> {
+    let name = from
+        .split('<')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .trim_matches('"')
+        .trim();
+    if !name.is_empty() && !name.contains('@') {
+        return Some(name.to_string());
+    }
+    let address = from.trim_matches(|c| c == '<' || c == '>' || char::is_whitespace(c));
+    let domain = address.rsplit_once('@')?.1.trim_end_matches('>');
+    (!domain.is_empty()).then(|| domain.to_string())
+}
+
+/// Looks for an order number following the word "order", e.g. "Order #112-3456789-0123456".
+fn order_number(text: &str) -> Option<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    for (index, word) in words.iter().enumerate() {
+        let word = word.to_lowercase();
+        let rest = match word.strip_prefix("order") {
+            Some(rest) if rest.is_empty() || rest.starts_with(['#', ':']) => rest,
+            _ => continue,
+        };
+
+        let inline = rest.trim_start_matches(['#', ':']);
+        let following = words[index + 1..]
+            .iter()
+            .map(|word| word.to_lowercase())
+            .skip_while(|word| ORDER_FILLERS.contains(&word.as_str()));
+        for token in std::iter::once(inline.to_string()).chain(following).take(2) {
+            let token = token
+                .trim_start_matches(['#', ':'])
+                .trim_end_matches(|c: char| !c.is_ascii_alphanumeric());
+            let looks_like_order = token.len() >= 4
+                && token.chars().any(|c| c.is_ascii_digit())
+                && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            if looks_like_order {
+                return Some(token.to_uppercase());
+            }
+        }
+    }
+    None
+}
+
+fn tracking_candidates(text: &str) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = text
+        .split_whitespace()
+        .filter(|word| is_url(word))
+        .filter_map(parse_tracking_url)
+        .filter_map(|url| Some(Candidate { carrier: url.carrier?, number: url.number }))
+        .collect();
+    for candidate in extract_tracking_numbers(text) {
+        if !candidates.iter().any(|c| c.number == candidate.number) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+/// Finds the tracking numbers of every message in an .eml or mbox file, keeping the first
+/// email that mentions each number.
+pub fn email_packages(raw: &[u8]) -> Vec<EmailPackage> {
+    let mut packages: Vec<EmailPackage> = Vec::new();
+    for message in read_messages(raw) {
+        let merchant = message.from.as_deref().and_then(merchant);
+        let order = order_number(&message.text)
+            .or_else(|| message.subject.as_deref().and_then(order_number));
+
+        let subject = message.subject.clone().unwrap_or_default();
+        for candidate in tracking_candidates(&format!("{}\n{}", subject, message.text)) {
+            if packages.iter().any(|package| package.candidate.number == candidate.number) {
+                continue;
+            }
+            packages.push(EmailPackage {
+                candidate,
+                subject: message.subject.clone().filter(|subject| !subject.is_empty()),
+                merchant: merchant.clone(),
+                order: order.clone(),
+            });
+        }
+    }
+    packages
+}
+
+/// Hands the packages of each unprocessed email in the watched folder to `on_packages`, then
+/// flags the message on the server so it is not read again. Messages are parsed off the main
+/// thread.
+pub async fn check_mailbox(
+    settings: &MailboxSettings,
+    mut on_packages: impl FnMut(Vec<EmailPackage>),
+) -> Result<(), Box<dyn Error>> {
+    let password = load_password(&settings.password_id).await?.unwrap_or_default();
+    let mut client = ImapClient::connect(&settings.host, settings.port, settings.use_tls).await?;
+    client.login(&settings.username, &password).await?;
+    client.select(&settings.folder).await?;
+
+    let since = (Local::now() - Duration::days(MAILBOX_LOOKBACK_DAYS)).date_naive();
+    for uid in client.search_unprocessed(since).await? {
+        if let Some(raw) = client.fetch(uid).await? {
+            let packages = gio::spawn_blocking(move || email_packages(&raw))
+                .await
+                .unwrap_or_default();
+            on_packages(packages);
+        }
+        client.mark_processed(uid).await?;
+    }
+    client.logout().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mbox_is_split_on_from_lines() {
+        let raw = b"From shop@example.com Mon Jan  1 00:00:00 2024\n\
+Subject: one\n\
+\n\
+>From the warehouse\n\
+>>From nowhere\n\
+From tracking@example.com Tue Jan  2 00:00:00 2024\n\
+Subject: two\n\
+\n\
+second\n";
+        assert!(is_mbox(raw));
+        let messages = split_mbox(raw);
+        assert_eq!(
+            messages,
+            vec![
+                b"Subject: one\n\nFrom the warehouse\n>From nowhere\n".to_vec(),
+                b"Subject: two\n\nsecond\n".to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn order_numbers() {
+        assert_eq!(order_number("Order #112-3456789-0123456 shipped"), Some("112-3456789-0123456".to_string()));
+        assert_eq!(order_number("Your order no. ab1234 is on its way"), Some("AB1234".to_string()));
+        assert_eq!(order_number("order:98765."), Some("98765".to_string()));
+        assert_eq!(order_number("Thank you for your order today"), None);
+        assert_eq!(order_number("Your orders 12345"), None);
+    }
+
+    #[test]
+    fn merchants() {
+        assert_eq!(merchant("\"Shop\" <orders@shop.example>"), Some("Shop".to_string()));
+        assert_eq!(merchant("<orders@shop.example>"), Some("shop.example".to_string()));
+        assert_eq!(merchant("orders@shop.example"), Some("shop.example".to_string()));
+    }
+
+    #[test]
+    fn packages_from_an_email() {
+        let raw = b"From: Shop <orders@shop.example>\n\
+Subject: Order 4521 has shipped\n\
+\n\
+Track it at https://www.ups.com/track?tracknum=1Z999AA10123456784\n\
+or with Royal Mail: RR123456785GB\n";
+        let packages = email_packages(raw);
+        let numbers: Vec<&str> = packages.iter().map(|package| package.candidate.number.as_str()).collect();
+        assert_eq!(numbers, ["1Z999AA10123456784", "RR123456785GB"]);
+        assert_eq!(packages[0].notes(), "Merchant: Shop\nOrder: 4521");
+        assert_eq!(packages[0].sender_nickname().as_deref(), Some("Shop: Order 4521 has shipped"));
+    }
+}
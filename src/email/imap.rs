@@ -0,0 +1,264 @@
+use chrono::NaiveDate;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_native_tls::{native_tls, TlsConnector};
+
+/// Keyword flag set on messages once their tracking numbers were added.
+const PROCESSED_KEYWORD: &str = "SptProcessed";
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+#[derive(Debug, Error)]
+pub enum ImapError {
+    #[error("Connection failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Secure connection failed: {0}")]
+    Tls(#[from] native_tls::Error),
+    #[error("Server refused {command}: {message}")]
+    Refused { command: String, message: String },
+    #[error("Server closed the connection")]
+    Closed,
+}
+
+/// Size of the `{n}` literal announced at the end of a response line, if any.
+fn literal_size(line: &[u8]) -> Option<usize> {
+    let line = line.strip_suffix(b"\r\n").or(line.strip_suffix(b"\n"))?;
+    let inner = line.strip_suffix(b"}")?;
+    let start = inner.iter().rposition(|&b| b == b'{')?;
+    let digits = &inner[start + 1..];
+    let digits = digits.strip_suffix(b"+").unwrap_or(digits);
+    std::str::from_utf8(digits).ok()?.parse().ok()
+}
+
+/// The command without its arguments, which may hold the password.
+fn command_name(command: &str) -> String {
+    let words = if command.starts_with("UID ") { 2 } else { 1 };
+    command.split(' ').take(words).collect::<Vec<_>>().join(" ")
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// A minimal IMAP4rev1 client, covering what the mailbox watcher needs.
+pub struct ImapClient {
+    stream: BufReader<Box<dyn Stream>>,
+    next_tag: u32,
+}
+
+impl ImapClient {
+    /// Connects over TLS, or over plain TCP for local test servers.
+    pub async fn connect(host: &str, port: u16, use_tls: bool) -> Result<Self, ImapError> {
+        let tcp = TcpStream::connect((host, port)).await?;
+        let stream: Box<dyn Stream> = if use_tls {
+            let connector = TlsConnector::from(native_tls::TlsConnector::new()?);
+            Box::new(connector.connect(host, tcp).await?)
+        } else {
+            Box::new(tcp)
+        };
+
+        let mut client = Self {
+            stream: BufReader::new(stream),
+            next_tag: 0,
+        };
+        let greeting = client.read_response().await?;
+        if !greeting.starts_with(b"* OK") && !greeting.starts_with(b"* PREAUTH") {
+            return Err(ImapError::Refused {
+                command: "connection".to_string(),
+                message: String::from_utf8_lossy(&greeting).trim().to_string(),
+            });
+        }
+        Ok(client)
+    }
+
+    /// Reads one response, following `{n}` literals so message bodies stay inside it.
+    async fn read_response(&mut self) -> Result<Vec<u8>, ImapError> {
+        let mut response = Vec::new();
+        loop {
+            let mut line = Vec::new();
+            if self.stream.read_until(b'\n', &mut line).await? == 0 {
+                return Err(ImapError::Closed);
+            }
+            response.extend_from_slice(&line);
+
+            let Some(size) = literal_size(&line) else {
+                return Ok(response);
+            };
+            let mut literal = vec![0; size];
+            self.stream.read_exact(&mut literal).await?;
+            response.extend_from_slice(&literal);
+        }
+    }
+
+    /// Sends a tagged command and returns its untagged responses once the server completed it.
+    async fn command(&mut self, command: &str) -> Result<Vec<Vec<u8>>, ImapError> {
+        self.next_tag += 1;
+        let tag = format!("A{} ", self.next_tag);
+        let stream = self.stream.get_mut();
+        stream.write_all(format!("{}{}\r\n", tag, command).as_bytes()).await?;
+        stream.flush().await?;
+
+        let mut untagged = Vec::new();
+        loop {
+            let response = self.read_response().await?;
+            let Some(status) = response.strip_prefix(tag.as_bytes()) else {
+                untagged.push(response);
+                continue;
+            };
+            if status.starts_with(b"OK") {
+                return Ok(untagged);
+            }
+            return Err(ImapError::Refused {
+                command: command_name(command),
+                message: String::from_utf8_lossy(status).trim().to_string(),
+            });
+        }
+    }
+
+    pub async fn login(&mut self, username: &str, password: &str) -> Result<(), ImapError> {
+        self.command(&format!("LOGIN {} {}", quote(username), quote(password)))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn select(&mut self, folder: &str) -> Result<(), ImapError> {
+        self.command(&format!("SELECT {}", quote(folder))).await.map(|_| ())
+    }
+
+    /// UIDs of the messages received since `since` that were not processed yet.
+    pub async fn search_unprocessed(&mut self, since: NaiveDate) -> Result<Vec<u32>, ImapError> {
+        let responses = self
+            .command(&format!(
+                "UID SEARCH NOT KEYWORD {} SINCE {}",
+                PROCESSED_KEYWORD,
+                since.format("%-d-%b-%Y")
+            ))
+            .await?;
+        let mut uids = Vec::new();
+        for response in responses {
+            if let Some(found) = response.strip_prefix(b"* SEARCH") {
+                let found = String::from_utf8_lossy(found);
+                uids.extend(found.split_whitespace().filter_map(|uid| uid.parse::<u32>().ok()));
+            }
+        }
+        Ok(uids)
+    }
+
+    /// The raw message, fetched without marking it as seen.
+    pub async fn fetch(&mut self, uid: u32) -> Result<Option<Vec<u8>>, ImapError> {
+        let responses = self.command(&format!("UID FETCH {} BODY.PEEK[]", uid)).await?;
+        for response in responses {
+            let Some(line_end) = response.iter().position(|&b| b == b'\n') else {
+                continue;
+            };
+            let Some(size) = literal_size(&response[..=line_end]) else {
+                continue;
+            };
+            let body = response.get(line_end + 1..line_end + 1 + size);
+            if let Some(body) = body {
+                return Ok(Some(body.to_vec()));
+            }
+        }
+        Ok(None)
+    }
+
+    pub async fn mark_processed(&mut self, uid: u32) -> Result<(), ImapError> {
+        self.command(&format!("UID STORE {} +FLAGS.SILENT ({})", uid, PROCESSED_KEYWORD))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn logout(mut self) -> Result<(), ImapError> {
+        self.command("LOGOUT").await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn literal_sizes() {
+        assert_eq!(literal_size(b"* 1 FETCH (UID 7 BODY[] {42}\r\n"), Some(42));
+        assert_eq!(literal_size(b"A1 LOGIN {5+}\n"), Some(5));
+        assert_eq!(literal_size(b"* OK ready\r\n"), None);
+    }
+
+    #[test]
+    fn passwords_stay_out_of_errors() {
+        assert_eq!(command_name("LOGIN \"test\" \"secret\""), "LOGIN");
+        assert_eq!(command_name("UID FETCH 7 BODY.PEEK[]"), "UID FETCH");
+        assert_eq!(quote("a \"b\" \\c"), "\"a \\\"b\\\" \\\\c\"");
+    }
+
+    /// Answers the commands of one client the way an IMAP server would.
+    async fn fake_server(listener: TcpListener, message: &'static str) {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (read, mut write) = socket.into_split();
+        let mut lines = BufReader::new(read).lines();
+        write.write_all(b"* OK ready\r\n").await.unwrap();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let (tag, command) = line.split_once(' ').unwrap();
+            let untagged = match command {
+                "LOGIN \"test\" \"wrong\"" => {
+                    let reply = format!("{} NO [AUTHENTICATIONFAILED] Invalid credentials\r\n", tag);
+                    write.write_all(reply.as_bytes()).await.unwrap();
+                    continue;
+                }
+                "SELECT \"INBOX\"" => "* 2 EXISTS\r\n".to_string(),
+                command if command.starts_with("UID SEARCH NOT KEYWORD SptProcessed SINCE ") => {
+                    "* SEARCH 7 9\r\n".to_string()
+                }
+                "UID FETCH 7 BODY.PEEK[]" => {
+                    format!("* 1 FETCH (UID 7 BODY[] {{{}}}\r\n{})\r\n", message.len(), message)
+                }
+                "LOGOUT" => "* BYE\r\n".to_string(),
+                _ => String::new(),
+            };
+            let reply = format!("{}{} OK done\r\n", untagged, tag);
+            write.write_all(reply.as_bytes()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn client_against_a_fake_server() {
+        let message = "Subject: Shipped\r\n\r\nRR123456785GB\r\n";
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(fake_server(listener, message));
+
+        let mut client = ImapClient::connect("127.0.0.1", port, false).await.unwrap();
+        let error = client.login("test", "wrong").await.unwrap_err();
+        assert!(matches!(error, ImapError::Refused { ref command, .. } if command == "LOGIN"));
+        assert!(!error.to_string().contains("wrong"));
+
+        client.login("test", "secret").await.unwrap();
+        client.select("INBOX").await.unwrap();
+        let since = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert_eq!(client.search_unprocessed(since).await.unwrap(), vec![7, 9]);
+        assert_eq!(client.fetch(7).await.unwrap(), Some(message.as_bytes().to_vec()));
+        assert_eq!(client.fetch(9).await.unwrap(), None);
+        client.mark_processed(7).await.unwrap();
+        client.logout().await.unwrap();
+        server.await.unwrap();
+    }
+
+    /// Runs against the GreenMail server described in the README, holding at least one unprocessed email.
+    #[tokio::test]
+    #[ignore]
+    async fn client_against_greenmail() {
+        let mut client = ImapClient::connect("localhost", 3143, false).await.unwrap();
+        client.login("test", "secret").await.unwrap();
+        client.select("INBOX").await.unwrap();
+        let since = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let uids = client.search_unprocessed(since).await.unwrap();
+        assert!(!uids.is_empty());
+        for uid in uids {
+            assert!(client.fetch(uid).await.unwrap().is_some());
+        }
+        client.logout().await.unwrap();
+    }
+}
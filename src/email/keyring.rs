@@ -0,0 +1,49 @@
+use crate::storage::{load_settings, save_settings};
+use adw::glib;
+use oo7::{Keyring, XDG_SCHEMA_ATTRIBUTE};
+use std::collections::HashMap;
+
+const SCHEMA: &str = "io.github.alrick.simple_package_tracker.Mailbox";
+const LABEL: &str = "Simple Package Tracker mailbox password";
+
+/// Attributes of the keyring item that `password_id` in the mailbox settings refers to.
+fn attributes(id: &str) -> HashMap<&str, &str> {
+    HashMap::from([(XDG_SCHEMA_ATTRIBUTE, SCHEMA), ("id", id)])
+}
+
+pub async fn load_password(id: &str) -> oo7::Result<Option<String>> {
+    if id.is_empty() {
+        return Ok(None);
+    }
+    let keyring = Keyring::new().await?;
+    let Some(item) = keyring.search_items(&attributes(id)).await?.into_iter().next() else {
+        return Ok(None);
+    };
+    if item.is_locked().await? {
+        item.unlock().await?;
+    }
+    let secret = item.secret().await?;
+    Ok(Some(String::from_utf8_lossy(&secret).into_owned()))
+}
+
+/// Saves the mailbox password in the keyring, creating the settings reference the first time.
+/// An empty password removes it instead.
+pub async fn save_password(password: &str) -> oo7::Result<()> {
+    let id = load_settings().mailbox.password_id;
+    let keyring = Keyring::new().await?;
+    let id = if password.is_empty() {
+        if !id.is_empty() {
+            keyring.delete(&attributes(&id)).await?;
+        }
+        String::new()
+    } else {
+        let id = if id.is_empty() { glib::uuid_string_random().to_string() } else { id };
+        keyring.create_item(LABEL, &attributes(&id), password, true).await?;
+        id
+    };
+
+    let mut settings = load_settings();
+    settings.mailbox.password_id = id;
+    let _ = save_settings(&settings);
+    Ok(())
+}
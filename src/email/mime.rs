@@ -0,0 +1,405 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use encoding_rs::{Encoding, UTF_8};
+
+/// Parts nested deeper than this are skipped, so a crafted message cannot exhaust the stack.
+const MAX_NESTING: usize = 32;
+
+/// A message reduced to what the importer reads: a few headers and the text of its body parts.
+pub struct Message {
+    pub subject: Option<String>,
+    pub from: Option<String>,
+    pub text: String,
+}
+
+struct Part<'a> {
+    headers: Vec<(String, String)>,
+    body: &'a [u8],
+}
+
+impl Part<'_> {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn position(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn lines(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    bytes
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+fn split_part(raw: &[u8]) -> Part<'_> {
+    let (head, body) = match (position(raw, b"\r\n\r\n"), position(raw, b"\n\n")) {
+        (Some(crlf), Some(lf)) if lf < crlf => (&raw[..lf], &raw[lf + 2..]),
+        (Some(crlf), _) => (&raw[..crlf], &raw[crlf + 4..]),
+        (None, Some(lf)) => (&raw[..lf], &raw[lf + 2..]),
+        (None, None) => (raw, &raw[raw.len()..]),
+    };
+
+    // Folded header lines continue with leading whitespace.
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in lines(head) {
+        let line = String::from_utf8_lossy(line);
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    Part { headers, body }
+}
+
+/// Splits a header such as Content-Type into its value and lowercase-named parameters.
+fn header_params(value: &str) -> (String, Vec<(String, String)>) {
+    let mut parts = value.split(';');
+    let main = parts.next().unwrap_or_default().trim().to_lowercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(name, value)| {
+            (name.trim().to_lowercase(), value.trim().trim_matches('"').to_string())
+        })
+        .collect();
+    (main, params)
+}
+
+fn hex_value(digit: u8) -> Option<u8> {
+    (digit as char).to_digit(16).map(|value| value as u8)
+}
+
+pub fn decode_quoted_printable(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] != b'=' {
+            output.push(input[i]);
+            i += 1;
+            continue;
+        }
+        match input.get(i + 1..i + 3) {
+            Some([b'\r', b'\n']) => i += 3,
+            Some([b'\n', _]) => i += 2,
+            Some(&[high, low]) => match (hex_value(high), hex_value(low)) {
+                (Some(high), Some(low)) => {
+                    output.push(high << 4 | low);
+                    i += 3;
+                }
+                _ => {
+                    output.push(b'=');
+                    i += 1;
+                }
+            },
+            // A soft break at the very end of the body.
+            _ if input.get(i + 1) == Some(&b'\n') => i += 2,
+            _ => {
+                output.push(b'=');
+                i += 1;
+            }
+        }
+    }
+    output
+}
+
+fn decode_base64(input: &[u8]) -> Vec<u8> {
+    let compact: Vec<u8> = input
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+    STANDARD.decode(compact).unwrap_or_default()
+}
+
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .and_then(|charset| Encoding::for_label(charset.as_bytes()))
+        .unwrap_or(UTF_8);
+    encoding.decode(bytes).0.into_owned()
+}
+
+/// Decodes RFC 2047 encoded words, e.g. "=?UTF-8?Q?Your_order_has_shipped?=".
+pub fn decode_header(value: &str) -> String {
+    let mut output = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].splitn(3, '?').collect::<Vec<_>>();
+        let [charset, encoding, tail] = decoded[..] else {
+            break;
+        };
+        let Some(end) = tail.find("?=") else {
+            break;
+        };
+
+        // Whitespace between two encoded words is not part of the text.
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            output.push_str(between);
+        }
+
+        let text = &tail[..end];
+        let bytes = if encoding.eq_ignore_ascii_case("b") {
+            decode_base64(text.as_bytes())
+        } else {
+            decode_quoted_printable(text.replace('_', " ").as_bytes())
+        };
+        output.push_str(&decode_charset(&bytes, Some(charset)));
+
+        let consumed = start + 2 + charset.len() + encoding.len() + 2 + end + 2;
+        rest = &rest[consumed..];
+        after_word = true;
+    }
+    output.push_str(rest);
+    output
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let number = entity.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 8)
+            .and_then(|end| Some((decode_entity(&rest[1..end + 1])?, end + 2)));
+        match decoded {
+            Some((c, length)) => {
+                output.push(c);
+                rest = &rest[length..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Reduces HTML to its text, keeping link targets since carriers often only put the number in a link.
+fn html_to_text(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        let tag_end = rest[start..].find('>').map_or(rest.len(), |end| start + end + 1);
+        let tag = &rest[start..tag_end];
+        let lowercase = tag.to_ascii_lowercase();
+        rest = &rest[tag_end..];
+
+        for skipped in ["style", "script"] {
+            if lowercase.starts_with(&format!("<{}", skipped)) {
+                let closing = format!("</{}", skipped);
+                let end = rest.to_ascii_lowercase().find(&closing).unwrap_or(rest.len());
+                rest = &rest[end..];
+            }
+        }
+
+        if let Some(href) = lowercase.find("href=") {
+            let value = &tag[href + 5..];
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'');
+            let link = match quote {
+                Some(quote) => value[1..].split(quote).next(),
+                None => value.split([' ', '>']).next(),
+            };
+            if let Some(link) = link {
+                output.push('\n');
+                output.push_str(link);
+            }
+        }
+        output.push('\n');
+    }
+    output.push_str(rest);
+    decode_entities(&output)
+}
+
+fn collect_text(part: &Part, text: &mut String, depth: usize) {
+    if depth > MAX_NESTING {
+        return;
+    }
+    let (content_type, params) = header_params(part.header("Content-Type").unwrap_or("text/plain"));
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    if content_type.starts_with("multipart/") {
+        let Some(boundary) = param("boundary") else {
+            return;
+        };
+        let delimiter = format!("--{}", boundary);
+        let mut current: Option<usize> = None;
+        let mut offset = 0;
+        for line in part.body.split_inclusive(|&b| b == b'\n') {
+            let trimmed = line.trim_ascii_end();
+            if trimmed.starts_with(delimiter.as_bytes()) {
+                if let Some(start) = current {
+                    collect_text(&split_part(&part.body[start..offset]), text, depth + 1);
+                }
+                if trimmed.ends_with(b"--") && trimmed.len() == delimiter.len() + 2 {
+                    return;
+                }
+                current = Some(offset + line.len());
+            }
+            offset += line.len();
+        }
+        if let Some(start) = current {
+            collect_text(&split_part(&part.body[start..]), text, depth + 1);
+        }
+        return;
+    }
+
+    if content_type == "message/rfc822" {
+        collect_text(&split_part(part.body), text, depth + 1);
+        return;
+    }
+    if !content_type.starts_with("text/") {
+        return;
+    }
+
+    let encoding = part
+        .header("Content-Transfer-Encoding")
+        .unwrap_or_default()
+        .to_lowercase();
+    let body = match encoding.trim() {
+        "base64" => decode_base64(part.body),
+        "quoted-printable" => decode_quoted_printable(part.body),
+        _ => part.body.to_vec(),
+    };
+    let decoded = decode_charset(&body, param("charset"));
+
+    if content_type == "text/html" {
+        text.push_str(&html_to_text(&decoded));
+    } else {
+        text.push_str(&decoded);
+    }
+    text.push('\n');
+}
+
+/// Parses a single RFC 5322 message, decoding its plain text and HTML parts.
+pub fn parse_message(raw: &[u8]) -> Message {
+    let part = split_part(raw);
+    let mut text = String::new();
+    collect_text(&part, &mut text, 0);
+
+    Message {
+        subject: part.header("Subject").map(decode_header),
+        from: part.header("From").map(decode_header),
+        text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoded_words() {
+        assert_eq!(decode_header("=?UTF-8?Q?Your_order_has_shipped?="), "Your order has shipped");
+        assert_eq!(decode_header("=?utf-8?b?Q29tbWFuZGUgZXhww6lkacOpZQ==?="), "Commande expédiée");
+        assert_eq!(decode_header("=?ISO-8859-1?Q?Caf=E9?= order"), "Café order");
+        assert_eq!(decode_header("=?UTF-8?Q?Hello?= =?UTF-8?Q?_world?="), "Hello world");
+        assert_eq!(decode_header("Plain subject"), "Plain subject");
+        assert_eq!(decode_header("Broken =?UTF-8?Q?word"), "Broken =?UTF-8?Q?word");
+    }
+
+    #[test]
+    fn quoted_printable() {
+        assert_eq!(decode_quoted_printable(b"a=3Db=\r\nc=\nd"), b"a=bcd");
+        assert_eq!(decode_quoted_printable(b"100=\n"), b"100");
+        assert_eq!(decode_quoted_printable(b"=ZZ="), b"=ZZ=");
+    }
+
+    #[test]
+    fn multipart_message() {
+        let raw = b"From: \"Shop\" <orders@shop.example>\r\n\
+Subject: =?UTF-8?Q?Your_order?=\r\n\
+\x20 has shipped\r\n\
+Content-Type: multipart/alternative; boundary=\"b1\"\r\n\
+\r\n\
+preamble\r\n\
+--b1\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+Order #112-3456789-0123456 is on its way=\r\n\
+\x20with Royal Mail.\r\n\
+--b1\r\n\
+Content-Type: text/html\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+PHA+VHJhY2sgaXQgPGEgaHJlZj0iaHR0cHM6Ly93d3cudXBzLmNvbS90cmFjaz90cmFja251bT0x\r\n\
+Wjk5OUFBMTAxMjM0NTY3ODQiPmhlcmU8L2E+ICZhbXA7IHNtaWxlPC9wPg==\r\n\
+--b1--\r\n\
+epilogue\r\n";
+        let message = parse_message(raw);
+        assert_eq!(message.subject.as_deref(), Some("Your order has shipped"));
+        assert_eq!(message.from.as_deref(), Some("\"Shop\" <orders@shop.example>"));
+        assert!(message.text.contains("Order #112-3456789-0123456 is on its way with Royal Mail."));
+        assert!(message.text.contains("https://www.ups.com/track?tracknum=1Z999AA10123456784"));
+        assert!(message.text.contains("& smile"));
+        assert!(!message.text.contains("preamble"));
+        assert!(!message.text.contains("epilogue"));
+    }
+
+    #[test]
+    fn attachments_are_skipped() {
+        let raw = b"Subject: Label\n\
+Content-Type: multipart/mixed; boundary=x\n\
+\n\
+--x\n\
+Content-Type: text/plain\n\
+\n\
+See the label.\n\
+--x\n\
+Content-Type: application/pdf\n\
+\n\
+%PDF-1.4\n\
+--x--\n";
+        let message = parse_message(raw);
+        assert_eq!(message.text, "See the label.\n\n");
+    }
+
+    #[test]
+    fn deep_nesting_is_skipped() {
+        let wrapped = |depth: usize| {
+            "Content-Type: message/rfc822\n\n".repeat(depth)
+                + "Content-Type: text/plain\n\nnested text\n"
+        };
+        assert!(parse_message(wrapped(MAX_NESTING).as_bytes()).text.contains("nested text"));
+        assert_eq!(parse_message(wrapped(MAX_NESTING + 1).as_bytes()).text, "");
+        assert_eq!(parse_message(wrapped(10_000).as_bytes()).text, "");
+    }
+}
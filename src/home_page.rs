@@ -1,2 +1,10 @@
+pub mod candidates_dialog;
+pub mod csv_import;
+pub mod export;
+pub mod quick_add;
+pub mod search;
+pub mod sections;
+pub mod sorting;
+pub mod status_badge;
 pub mod tracking_input;
 pub mod tracking_list;
\ No newline at end of file
@@ -0,0 +1,68 @@
+use crate::tracking_number::extract::Candidate;
+use adw::{
+    gtk::{Align, CheckButton, ListBox, SelectionMode},
+    prelude::*,
+    ActionRow, AlertDialog, ResponseAppearance,
+};
+
+/// Lets the user confirm the tracking numbers found in `source`, such as pasted text, before they are added.
+pub fn create_candidates_dialog(
+    candidates: &[Candidate],
+    source: &str,
+    on_confirm: impl Fn(Vec<String>) + 'static,
+) -> AlertDialog {
+    let list = ListBox::builder()
+        .css_classes(vec!["boxed-list"])
+        .selection_mode(SelectionMode::None)
+        .build();
+
+    let mut checks = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let check = CheckButton::builder()
+            .active(true)
+            .valign(Align::Center)
+            .build();
+        let row = ActionRow::builder()
+            .title(&candidate.number)
+            .subtitle(candidate.carrier.name())
+            .use_markup(false)
+            .activatable_widget(&check)
+            .build();
+        row.add_prefix(&check);
+        list.append(&row);
+        checks.push((candidate.number.clone(), check));
+    }
+
+    let body = match candidates.len() {
+        1 => format!("1 tracking number was found in {}.", source),
+        count => format!("{} tracking numbers were found in {}.", count, source),
+    };
+
+    let dialog = AlertDialog::builder()
+        .heading("Add Tracking Numbers?")
+        .body(&body)
+        .close_response("cancel")
+        .default_response("add")
+        .extra_child(&list)
+        .build();
+
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("add", "Add");
+    dialog.set_response_appearance("add", ResponseAppearance::Suggested);
+
+    dialog.connect_response(None, move |_, response| {
+        if response != "add" {
+            return;
+        }
+        let numbers: Vec<String> = checks
+            .iter()
+            .filter(|(_, check)| check.is_active())
+            .map(|(number, _)| number.clone())
+            .collect();
+        if !numbers.is_empty() {
+            on_confirm(numbers);
+        }
+    });
+
+    dialog
+}
@@ -0,0 +1,129 @@
+use crate::tracking_number::normalize::normalize;
+
+/// A package read from a CSV file, such as one written by the export action.
+pub struct ImportedPackage {
+    pub number: String,
+    pub nickname: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Splits CSV text into records, handling quoted fields with commas, quotes and line breaks.
+fn parse_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn non_empty(field: Option<&String>) -> Option<String> {
+    field.map(|field| field.trim()).filter(|field| !field.is_empty()).map(str::to_string)
+}
+
+/// Reads packages from CSV with a `number` column and optional `nickname` and `notes` columns,
+/// or, without a header, from the first column.
+pub fn parse_packages_csv(text: &str) -> Vec<ImportedPackage> {
+    let mut records = parse_records(text.trim_start_matches('\u{feff}')).into_iter();
+    let Some(first) = records.next() else {
+        return Vec::new();
+    };
+
+    let column = |name: &str| {
+        first
+            .iter()
+            .position(|header| header.trim().eq_ignore_ascii_case(name))
+    };
+    let (number_column, nickname_column, notes_column, header) = match column("number") {
+        Some(number_column) => (number_column, column("nickname"), column("notes"), None),
+        None => (0, None, None, Some(first.clone())),
+    };
+
+    let mut packages: Vec<ImportedPackage> = Vec::new();
+    for record in header.into_iter().chain(records) {
+        let number = normalize(record.get(number_column).map_or("", String::as_str));
+        if number.is_empty() || packages.iter().any(|package| package.number == number) {
+            continue;
+        }
+        packages.push(ImportedPackage {
+            number,
+            nickname: nickname_column.and_then(|column| non_empty(record.get(column))),
+            notes: notes_column.and_then(|column| non_empty(record.get(column))),
+        });
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(packages: &[ImportedPackage]) -> Vec<(&str, Option<&str>, Option<&str>)> {
+        packages
+            .iter()
+            .map(|package| {
+                (package.number.as_str(), package.nickname.as_deref(), package.notes.as_deref())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quoted_fields() {
+        let records = parse_records("a,\"b, \"\"c\"\"\",\"d\ne\"\r\nf,,\n");
+        assert_eq!(
+            records,
+            vec![
+                vec!["a".to_string(), "b, \"c\"".to_string(), "d\ne".to_string()],
+                vec!["f".to_string(), String::new(), String::new()],
+            ]
+        );
+    }
+
+    #[test]
+    fn header_columns_in_any_order() {
+        let text = "\u{feff}Notes,Number,Nickname\nGift,1z999aa10123456784,Shoes\n,RR123456785GB,\n";
+        let packages = parse_packages_csv(text);
+        assert_eq!(
+            summary(&packages),
+            vec![
+                ("1Z999AA10123456784", Some("Shoes"), Some("Gift")),
+                ("RR123456785GB", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn without_header_the_first_column_is_read() {
+        let packages = parse_packages_csv("RR123456785GB,ignored\n1Z999AA10123456784\nrr123456785gb\n");
+        assert_eq!(
+            summary(&packages),
+            vec![("RR123456785GB", None, None), ("1Z999AA10123456784", None, None)]
+        );
+    }
+
+    #[test]
+    fn empty_input() {
+        assert!(parse_packages_csv("").is_empty());
+    }
+}
@@ -0,0 +1,38 @@
+use crate::package::Package;
+
+/// Serializes packages as CSV, one line per package with its latest known status.
+pub fn packages_to_csv(packages: &[Package]) -> String {
+    let mut csv = String::from("number,nickname,status,courier,last_event,last_event_date,notes\n");
+    for package in packages {
+        let info = package.info();
+        let latest_event = info.as_ref().and_then(|info| info.latest_event());
+        let fields = [
+            package.number(),
+            package.nickname().unwrap_or_default(),
+            info.as_ref().map(|info| info.status().to_string()).unwrap_or_default(),
+            info.as_ref()
+                .and_then(|info| info.courier())
+                .unwrap_or_default()
+                .to_string(),
+            latest_event
+                .and_then(|event| event.status.clone())
+                .unwrap_or_default(),
+            latest_event
+                .map(|event| event.occurrence_datetime.to_rfc3339())
+                .unwrap_or_default(),
+            package.notes(),
+        ];
+        let line: Vec<String> = fields.iter().map(|field| escape_csv_field(field)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
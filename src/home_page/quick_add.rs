@@ -0,0 +1,47 @@
+use crate::tracking_number::validate::{validate_input_line, Validation};
+use adw::gtk::{prelude::*, Box, Entry, EntryIconPosition, Label, Orientation};
+
+/// Names the carrier detected from the entry's text, or flags a check digit that does not match.
+fn update_carrier_hint(entry: &Entry, carrier_label: &Label) {
+    let (hint, invalid) = match validate_input_line(&entry.text()) {
+        Validation::Valid(carrier) => (carrier.name().to_string(), false),
+        Validation::Invalid(carrier) => (format!("Invalid {} number", carrier.name()), true),
+        Validation::Unrecognized => (String::new(), false),
+    };
+
+    carrier_label.set_label(&hint);
+    carrier_label.set_visible(!hint.is_empty());
+    if invalid {
+        entry.add_css_class("error");
+    } else {
+        entry.remove_css_class("error");
+    }
+}
+
+pub fn create_quick_add_entry() -> (Box, Entry) {
+    let entry = Entry::builder()
+        .placeholder_text("Add a tracking number")
+        .tooltip_markup("Press Enter to track a single package")
+        .width_chars(30)
+        .build();
+    entry.set_icon_from_icon_name(EntryIconPosition::Primary, Some("list-add-symbolic"));
+
+    let carrier_label = Label::builder()
+        .css_classes(vec!["dim-label", "caption"])
+        .visible(false)
+        .build();
+
+    let carrier_label_clone = carrier_label.clone();
+    entry.connect_changed(move |entry| {
+        update_carrier_hint(entry, &carrier_label_clone);
+    });
+
+    let quick_add = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(8)
+        .build();
+    quick_add.append(&entry);
+    quick_add.append(&carrier_label);
+
+    (quick_add, entry)
+}
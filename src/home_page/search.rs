@@ -0,0 +1,39 @@
+use crate::home_page::sections::Section;
+use crate::package::Package;
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub struct PackageFilter {
+    pub query: String,
+    pub sections: HashSet<Section>,
+}
+
+impl PackageFilter {
+    pub fn matches(&self, package: &Package) -> bool {
+        if !self.sections.is_empty() && !self.sections.contains(&package.section()) {
+            return false;
+        }
+
+        let query = self.query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+
+        let info = package.info();
+        let latest_event = info
+            .as_ref()
+            .and_then(|info| info.latest_event())
+            .and_then(|e| e.status.clone());
+        [
+            Some(package.number()),
+            package.nickname(),
+            Some(package.notes()),
+            info.as_ref().and_then(|info| info.courier()).map(str::to_string),
+            latest_event,
+            info.map(|info| info.label),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(&query))
+    }
+}
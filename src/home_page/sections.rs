@@ -0,0 +1,138 @@
+use crate::api::models::{StatusMilestone, TrackingInfo};
+use crate::package::Package;
+use crate::storage::{load_settings, save_settings};
+use adw::{
+    gio,
+    gtk::{Box, Expander, Label, ListBox, Orientation, SelectionMode, Widget},
+    prelude::*,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    NeedsAttention,
+    ArrivingToday,
+    InTransit,
+    WaitingForInfo,
+    Delivered,
+}
+
+impl Section {
+    pub const ALL: [Section; 5] = [
+        Self::NeedsAttention,
+        Self::ArrivingToday,
+        Self::InTransit,
+        Self::WaitingForInfo,
+        Self::Delivered,
+    ];
+
+    pub fn from_info(info: &TrackingInfo) -> Self {
+        if info.has_error {
+            return Self::WaitingForInfo;
+        }
+        match info.milestone() {
+            StatusMilestone::Exception
+            | StatusMilestone::FailedAttempt
+            | StatusMilestone::AvailableForPickup => Self::NeedsAttention,
+            StatusMilestone::OutForDelivery => Self::ArrivingToday,
+            StatusMilestone::InTransit => Self::InTransit,
+            StatusMilestone::Pending | StatusMilestone::InfoReceived => Self::WaitingForInfo,
+            StatusMilestone::Delivered => Self::Delivered,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::NeedsAttention => "Needs attention",
+            Self::ArrivingToday => "Arriving today",
+            Self::InTransit => "In transit",
+            Self::WaitingForInfo => "Waiting for info",
+            Self::Delivered => "Delivered",
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::NeedsAttention => "needs-attention",
+            Self::ArrivingToday => "arriving-today",
+            Self::InTransit => "in-transit",
+            Self::WaitingForInfo => "waiting-for-info",
+            Self::Delivered => "delivered",
+        }
+    }
+}
+
+/// Builds a collapsible section listing the packages of `model`, hidden while it is empty.
+pub fn create_section(
+    section: Section,
+    model: &impl IsA<gio::ListModel>,
+    create_row: impl Fn(&Package) -> Widget + 'static,
+) -> Expander {
+    let collapsed = load_settings()
+        .collapsed_sections
+        .iter()
+        .any(|key| key == section.key());
+
+    let title = Label::builder()
+        .label(section.title())
+        .css_classes(vec!["heading"])
+        .build();
+
+    let count = Label::builder()
+        .css_classes(vec!["dim-label"])
+        .build();
+
+    let header = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    header.append(&title);
+    header.append(&count);
+
+    let list = ListBox::builder()
+        .css_classes(vec!["boxed-list"])
+        .selection_mode(SelectionMode::None)
+        .margin_top(6)
+        .build();
+    list.bind_model(Some(model), move |object| {
+        let package = object
+            .downcast_ref::<Package>()
+            .expect("Section models only hold packages");
+        create_row(package)
+    });
+
+    let expander = Expander::builder()
+        .label_widget(&header)
+        .child(&list)
+        .expanded(!collapsed)
+        .build();
+
+    expander.connect_expanded_notify(move |expander| {
+        let mut settings = load_settings();
+        settings.collapsed_sections.retain(|key| key != section.key());
+        if !expander.is_expanded() {
+            settings.collapsed_sections.push(section.key().to_string());
+        }
+        let _ = save_settings(&settings);
+    });
+
+    set_section_count(&expander, &count, model.n_items());
+    let expander_weak = expander.downgrade();
+    model.connect_items_changed(move |model, _, _, _| {
+        if let Some(expander) = expander_weak.upgrade() {
+            set_section_count(&expander, &count, model.n_items());
+        }
+    });
+
+    expander
+}
+
+/// Shows the package count of a section, hiding the section once it is empty.
+fn set_section_count(expander: &Expander, count_label: &Label, count: u32) {
+    let text = if count == 1 {
+        "1 package".to_string()
+    } else {
+        format!("{} packages", count)
+    };
+    count_label.set_label(&text);
+    expander.set_visible(count > 0);
+}
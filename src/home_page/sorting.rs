@@ -0,0 +1,44 @@
+use crate::package::Package;
+use crate::storage::SortKey;
+use std::cmp::Ordering;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortValue {
+    Number(i64),
+    Text(String),
+}
+
+fn sort_value(key: SortKey, package: &Package) -> Option<SortValue> {
+    let info = package.info();
+    match key {
+        SortKey::DateAdded => Some(SortValue::Number(package.added_index().into())),
+        SortKey::LastEvent => info?
+            .latest_event()
+            .map(|event| SortValue::Number(event.occurrence_datetime.timestamp())),
+        SortKey::EstimatedDelivery => info?
+            .estimated_delivery
+            .map(|date| SortValue::Number(date.timestamp())),
+        SortKey::Severity => {
+            let severity = match info {
+                Some(info) if !info.has_error => info.milestone().severity() as i64 + 1,
+                _ => 0,
+            };
+            Some(SortValue::Number(severity))
+        }
+        SortKey::Nickname => Some(SortValue::Text(package.title().to_lowercase())),
+        SortKey::Courier => info?
+            .courier()
+            .map(|courier| SortValue::Text(courier.to_lowercase())),
+    }
+}
+
+/// Compares two packages by the given key, keeping packages without a value for it last.
+pub fn compare_packages(a: &Package, b: &Package, key: SortKey, descending: bool) -> Ordering {
+    match (sort_value(key, a), sort_value(key, b)) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -0,0 +1,60 @@
+use crate::api::models::TrackingInfo;
+use crate::package::Package;
+use adw::gtk::{Align, Image, Label};
+use adw::{glib, prelude::*};
+
+pub fn status_style(info: &TrackingInfo) -> (&'static str, &'static str, &'static str) {
+    if info.has_error {
+        return ("dialog-error-symbolic", "status-error", "Error");
+    }
+    let milestone = info.milestone();
+    (milestone.icon_name(), milestone.css_class(), milestone.to_string())
+}
+
+pub fn create_status_icon(info: &TrackingInfo) -> Image {
+    let (icon_name, css_class, _) = status_style(info);
+    Image::builder()
+        .icon_name(icon_name)
+        .css_classes(vec!["status-icon", css_class])
+        .valign(Align::Center)
+        .build()
+}
+
+pub fn create_status_pill(info: &TrackingInfo) -> Label {
+    let (_, css_class, label) = status_style(info);
+    Label::builder()
+        .label(label)
+        .css_classes(vec!["status-pill", css_class])
+        .valign(Align::Center)
+        .build()
+}
+
+/// Status icon that follows the package as its tracking info changes.
+pub fn create_bound_status_icon(package: &Package) -> Image {
+    let icon = Image::builder().valign(Align::Center).build();
+    package
+        .bind_property("status-icon", &icon, "icon-name")
+        .sync_create()
+        .build();
+    bind_status_class(package, &icon, "status-icon");
+    icon
+}
+
+/// Status pill that follows the package as its tracking info changes.
+pub fn create_bound_status_pill(package: &Package) -> Label {
+    let pill = Label::builder().valign(Align::Center).build();
+    package
+        .bind_property("status-label", &pill, "label")
+        .sync_create()
+        .build();
+    bind_status_class(package, &pill, "status-pill");
+    pill
+}
+
+fn bind_status_class(package: &Package, widget: &impl IsA<glib::Object>, base_class: &'static str) {
+    package
+        .bind_property("status-class", widget, "css-classes")
+        .transform_to(move |_, status_class: String| Some(vec![base_class.to_string(), status_class]))
+        .sync_create()
+        .build();
+}
@@ -1,6 +1,42 @@
-use adw::gtk::{Frame, ScrolledWindow, TextView, WrapMode};
+use crate::tracking_number::validate::{validate_input_line, Validation};
+use adw::{
+    gtk::{
+        pango::Underline, Align, Box, Button, Frame, Label, Orientation, ScrolledWindow,
+        TextBuffer, TextTag, TextView, WrapMode,
+    },
+    prelude::*,
+    Dialog, HeaderBar, ToolbarView,
+};
 
-pub fn create_input_area() -> (Frame, TextView) {
+/// Underlines lines whose check digit does not match the detected carrier and lists them below.
+fn flag_invalid_lines(buffer: &TextBuffer, validation_label: &Label) {
+    buffer.remove_tag_by_name("invalid", &buffer.start_iter(), &buffer.end_iter());
+
+    let mut messages = Vec::new();
+    for line_number in 0..buffer.line_count() {
+        let Some(start) = buffer.iter_at_line(line_number) else {
+            continue;
+        };
+        let mut end = start;
+        if !end.ends_line() {
+            end.forward_to_line_end();
+        }
+
+        if let Validation::Invalid(carrier) = validate_input_line(&buffer.text(&start, &end, false)) {
+            buffer.apply_tag_by_name("invalid", &start, &end);
+            messages.push(format!(
+                "Line {}: check digit does not match a {} tracking number",
+                line_number + 1,
+                carrier.name()
+            ));
+        }
+    }
+
+    validation_label.set_label(&messages.join("\n"));
+    validation_label.set_visible(!messages.is_empty());
+}
+
+fn create_input_area() -> (Box, TextView) {
     let text_field = TextView::builder()
         .bottom_margin(12)
         .left_margin(12)
@@ -11,6 +47,24 @@ pub fn create_input_area() -> (Frame, TextView) {
         .tooltip_markup("Enter one or more tracking numbers (one per line)")
         .build();
 
+    let invalid_tag = TextTag::builder()
+        .name("invalid")
+        .underline(Underline::Error)
+        .build();
+    text_field.buffer().tag_table().add(&invalid_tag);
+
+    let validation_label = Label::builder()
+        .css_classes(vec!["error"])
+        .halign(Align::Start)
+        .wrap(true)
+        .visible(false)
+        .build();
+
+    let validation_label_clone = validation_label.clone();
+    text_field.buffer().connect_changed(move |buffer| {
+        flag_invalid_lines(buffer, &validation_label_clone);
+    });
+
     let scroll_window = ScrolledWindow::builder()
         .child(&text_field)
         .height_request(180)
@@ -19,5 +73,46 @@ pub fn create_input_area() -> (Frame, TextView) {
 
     let frame = Frame::builder().child(&scroll_window).build();
 
-    return (frame, text_field);
+    let input_area = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(6)
+        .build();
+    input_area.append(&frame);
+    input_area.append(&validation_label);
+
+    return (input_area, text_field);
+}
+
+/// Dialog holding the multi-line input, for pasting many numbers or a whole shipping email at once.
+pub fn create_bulk_add_dialog() -> (Dialog, TextView, Button) {
+    let (input_area, text_field) = create_input_area();
+
+    let track_button = Button::builder()
+        .label("Track")
+        .width_request(200)
+        .height_request(25)
+        .halign(Align::Center)
+        .css_classes(vec!["suggested-action", "pill"])
+        .build();
+
+    let content = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(20)
+        .margin_start(20)
+        .margin_end(20)
+        .margin_bottom(20)
+        .build();
+    content.append(&input_area);
+    content.append(&track_button);
+
+    let toolbar = ToolbarView::new();
+    toolbar.add_top_bar(&HeaderBar::new());
+    toolbar.set_content(Some(&content));
+
+    let dialog = Dialog::builder()
+        .title("Add Tracking Numbers")
+        .child(&toolbar)
+        .build();
+
+    (dialog, text_field, track_button)
 }
@@ -1,158 +1,435 @@
+mod import;
+mod watchers;
+
+use crate::archive_page::archive::create_archive_page;
 use crate::details_page::details::create_details_page;
-use crate::api::tracking::TrackingClient;
-use crate::storage::{load_tracking_numbers, save_tracking_numbers};
-use adw::glib;
+use crate::home_page::export::packages_to_csv;
+use crate::home_page::quick_add::create_quick_add_entry;
+use crate::home_page::search::PackageFilter;
+use crate::home_page::sections::{create_section, Section};
+use crate::home_page::sorting::compare_packages;
+use crate::home_page::status_badge::{create_bound_status_icon, create_bound_status_pill};
+use crate::home_page::tracking_input::create_bulk_add_dialog;
+use crate::package::store::PackageStore;
+use crate::package::Package;
+use crate::storage::{load_settings, save_settings, update_package_metadata, SortKey};
+use crate::tracking_number::Carrier;
+use crate::tracking_number::normalize::normalize;
+use crate::tracking_number::url::resolve_urls;
+use crate::tracking_number::validate::{validate_input_line, Validation};
+use adw::{gio, glib};
 use adw::{
     gtk::{
-        Align, Box, Button, Frame, Label, ListBox, Orientation, ScrolledWindow, TextView,
-        ToggleButton,
+        gdk::BUTTON_SECONDARY, ActionBar, Align, Box, Button, CallbackAction,
+        CheckButton, CustomFilter, CustomSorter, DropTarget, Entry, EventSequenceState,
+        FileChooserAction, FileChooserNative, FilterChange, FilterListModel, Frame,
+        GestureClick, Label, MenuButton, NamedAction, Orientation, ResponseType, ScrolledWindow,
+        SearchBar, SearchEntry, Shortcut, ShortcutController, ShortcutTrigger,
+        SortListModel, SorterChange, ToggleButton, Widget, Window,
     },
     prelude::*,
-    ActionRow, AlertDialog, NavigationView, ResponseAppearance, Spinner, StatusPage,
+    ActionRow, AlertDialog, Banner, NavigationView, ResponseAppearance, Spinner, StatusPage, Toast,
+    ToastOverlay,
 };
+use import::{create_drop_target, create_track_action, open_import_files, track_text};
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::rc::Rc;
+use watchers::{watch_clipboard, watch_mailbox};
+
+#[derive(Clone)]
+struct PackageList {
+    nav_view: NavigationView,
+    frame: Frame,
+    no_package_title: StatusPage,
+    no_results_title: StatusPage,
+    list_view: ScrolledWindow,
+    toast_overlay: ToastOverlay,
+    store: PackageStore,
+    filtered: FilterListModel,
+    search_filter: CustomFilter,
+    section_filters: Vec<CustomFilter>,
+    sorter: CustomSorter,
+    filter: Rc<RefCell<PackageFilter>>,
+    sort_order: Rc<Cell<(SortKey, bool)>>,
+    selection_button: ToggleButton,
+    selection_count: Label,
+    selection_actions: Box,
+}
 
-fn handle_delete_numbers(
-    package: &ActionRow,
-    frame: &Frame,
-    no_package_title: &StatusPage,
-) -> AlertDialog {
-    let delete_dialog = AlertDialog::builder()
-        .heading("Delete?")
-        .body("Are you sure you want to remove this number from the list?")
-        .close_response("cancel")
-        .build();
+impl PackageList {
+    fn new(store: PackageStore, nav_view: NavigationView, toast_overlay: ToastOverlay) -> Self {
+        let no_package_title = StatusPage::builder()
+            .title("No tracked packages")
+            .description("Type a tracking number in the header bar and press Enter.")
+            .icon_name("system-search-symbolic")
+            .height_request(440)
+            .build();
+
+        let no_results_title = StatusPage::builder()
+            .title("No results found")
+            .description("Try a different search or status filter.")
+            .icon_name("edit-find-symbolic")
+            .height_request(440)
+            .build();
+
+        let frame = Frame::builder()
+            .child(&no_package_title)
+            .css_classes(vec!["boxed-list"])
+            .build();
+
+        let list_view = ScrolledWindow::builder()
+            .height_request(440)
+            .vexpand(false)
+            .build();
+
+        let filter = Rc::new(RefCell::new(PackageFilter::default()));
+        let settings = load_settings();
+        let sort_order = Rc::new(Cell::new((settings.sort_key, settings.sort_descending)));
+
+        let filter_clone = filter.clone();
+        let search_filter = CustomFilter::new(move |object| {
+            object
+                .downcast_ref::<Package>()
+                .is_some_and(|package| filter_clone.borrow().matches(package))
+        });
+        let filtered = FilterListModel::new(Some(store.model().clone()), Some(search_filter.clone()));
+
+        let sort_order_clone = sort_order.clone();
+        let sorter = CustomSorter::new(move |a, b| {
+            let (Some(a), Some(b)) = (a.downcast_ref::<Package>(), b.downcast_ref::<Package>()) else {
+                return std::cmp::Ordering::Equal.into();
+            };
+            let (key, descending) = sort_order_clone.get();
+            compare_packages(a, b, key, descending).into()
+        });
+
+        let section_filters = Section::ALL
+            .iter()
+            .map(|&section| {
+                CustomFilter::new(move |object| {
+                    object
+                        .downcast_ref::<Package>()
+                        .is_some_and(|package| package.section() == section)
+                })
+            })
+            .collect();
+
+        let selection_button = ToggleButton::builder()
+            .icon_name("selection-mode-symbolic")
+            .tooltip_markup("Select packages")
+            .width_request(40)
+            .height_request(25)
+            .valign(Align::End)
+            .build();
 
-    delete_dialog.add_response("cancel", "Cancel");
-    delete_dialog.add_response("remove", "Remove");
-    delete_dialog.set_response_appearance("remove", ResponseAppearance::Destructive);
-
-    if let Some(parent) = package.parent() {
-        if let Some(box_container) = parent.downcast_ref::<ListBox>() {
-            let box_container_clone = box_container.clone();
-            let package_clone = package.clone();
-            let frame_clone = frame.clone();
-            let no_title_clone = no_package_title.clone();
-
-            delete_dialog.connect_response(None, move |dialog, response| {
-                if response == "remove" {
-                    box_container_clone.remove(&package_clone);
-                    let mut remaining_numbers = Vec::new();
-                    let mut row_opt = box_container_clone.first_child();
-
-                    while let Some(row) = row_opt {
-                        if let Some(row) = row.downcast_ref::<ActionRow>() {
-                            remaining_numbers.push(row.title().to_string());
-                        }
-                        row_opt = row.next_sibling();
-                    }
-                    let _ = save_tracking_numbers(&remaining_numbers);
-
-                    if box_container_clone.first_child().is_none() {
-                        frame_clone.set_child(Some(&no_title_clone));
-                    }
+        let selection_count = Label::builder()
+            .css_classes(vec!["dim-label"])
+            .build();
+
+        let selection_actions = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(6)
+            .build();
+
+        let list = Self {
+            nav_view,
+            frame,
+            no_package_title,
+            no_results_title,
+            list_view,
+            toast_overlay,
+            store,
+            filtered,
+            search_filter,
+            section_filters,
+            sorter,
+            filter,
+            sort_order,
+            selection_button,
+            selection_count,
+            selection_actions,
+        };
+        list.build_sections();
+
+        let list_clone = list.clone();
+        list.selection_button.connect_toggled(move |button| {
+            if !button.is_active() {
+                for package in list_clone.store.packages() {
+                    package.set_selected(false);
                 }
-                dialog.close();
+            }
+            list_clone.update_selection();
+        });
+        list.update_selection();
+
+        let list_clone = list.clone();
+        list.store.connect_updated(move || {
+            list_clone.invalidate_filter();
+            list_clone.invalidate_sort();
+        });
+        let list_clone = list.clone();
+        list.store
+            .model()
+            .connect_items_changed(move |_, _, _, _| list_clone.update_view());
+        let list_clone = list.clone();
+        list.filtered
+            .connect_items_changed(move |_, _, _, _| list_clone.update_view());
+        list.update_view();
+
+        list
+    }
+
+    fn build_sections(&self) {
+        let sorted = SortListModel::new(Some(self.filtered.clone()), Some(self.sorter.clone()));
+
+        let sections = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+
+        for (section, section_filter) in Section::ALL.into_iter().zip(&self.section_filters) {
+            let section_model = FilterListModel::new(Some(sorted.clone()), Some(section_filter.clone()));
+            let list = self.clone();
+            let section_widget = create_section(section, &section_model, move |package| {
+                list.create_package_row(package).upcast()
             });
+            sections.append(&section_widget);
         }
+
+        self.list_view.set_child(Some(&sections));
     }
 
-    return delete_dialog;
-}
+    /// Shows the package list, or the matching placeholder when nothing is listed.
+    fn update_view(&self) {
+        if self.store.model().n_items() == 0 {
+            self.frame.set_child(Some(&self.no_package_title));
+        } else if self.filtered.n_items() == 0 {
+            self.frame.set_child(Some(&self.no_results_title));
+        } else {
+            self.frame.set_child(Some(&self.list_view));
+        }
+    }
 
-fn clean_numbers_list(input: &str) -> Vec<String> {
-    let existing_numbers = load_tracking_numbers();
-    let new_numbers: Vec<String> = input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .collect::<std::collections::HashSet<_>>()
-        .into_iter()
-        .map(String::from)
-        .filter(|num| !existing_numbers.contains(num))
-        .collect();
-
-    let all_numbers = if !input.is_empty() {
-        let mut combined = existing_numbers;
-        combined.extend(new_numbers);
-        combined
-    } else {
-        existing_numbers
-    };
-
-    return all_numbers;
-}
+    fn invalidate_filter(&self) {
+        self.search_filter.changed(FilterChange::Different);
+        for section_filter in &self.section_filters {
+            section_filter.changed(FilterChange::Different);
+        }
+    }
 
-fn show_loading_state() -> Box {
-    let loading_box = Box::builder()
-        .orientation(Orientation::Vertical)
-        .valign(Align::Center)
-        .halign(Align::Center)
-        .vexpand(false)
-        .spacing(10)
-        .height_request(440)
-        .build();
+    fn invalidate_sort(&self) {
+        self.sorter.changed(SorterChange::Different);
+    }
 
-    let loading_spinner = Spinner::builder()
-        .height_request(64)
-        .width_request(64)
-        .vexpand(true)
-        .valign(Align::End)
-        .build();
+    fn selected_packages(&self) -> Vec<Package> {
+        self.store
+            .packages()
+            .into_iter()
+            .filter(Package::selected)
+            .collect()
+    }
 
-    let loading_label = Label::builder()
-        .label("Loading packages...")
-        .css_classes(vec!["dim-label"])
-        .vexpand(true)
-        .valign(Align::Start)
-        .build();
+    fn update_selection(&self) {
+        let count = self.selected_packages().len();
+        self.selection_count.set_label(&format!("{} selected", count));
+        self.selection_actions.set_sensitive(count > 0);
+    }
 
-    loading_box.append(&loading_spinner);
-    loading_box.append(&loading_label);
-    return loading_box;
-}
+    /// Selects every package matching the current search and status filters.
+    fn select_all(&self) {
+        for package in self.filtered.iter::<Package>().filter_map(Result::ok) {
+            package.set_selected(true);
+        }
+        self.update_selection();
+    }
 
-async fn create_package_rows(
-    input: &str,
-    nav_view: &NavigationView,
-    frame: &Frame,
-    no_package_title: &StatusPage,
-) {
-    frame.set_child(Some(&show_loading_state()));
-
-    let list = ListBox::builder().css_classes(vec!["boxed-list"]).build();
-    let all_numbers = clean_numbers_list(input);
-
-    let client = TrackingClient::new();
-    let tracking_info = client.process_tracking_numbers(&all_numbers.join("\n")).await;
-    let numbers: Vec<String> = tracking_info
-        .iter()
-        .map(|info| info.id_ship.clone())
-        .collect();
-    let _ = save_tracking_numbers(&numbers);
-    let scrolled_window = ScrolledWindow::builder()
-        .child(&list)
-        .height_request(440)
-        .vexpand(false)
-        .build();
-    frame.set_child(Some(&scrolled_window));
-    for info in tracking_info {
-        let subtitle = if info.has_error {
-            if let Some(error) = &info.error_message {
-                error.clone()
-            } else {
-                "No tracking data available".to_string()
+    fn delete_packages(&self, packages: Vec<Package>) {
+        for package in &packages {
+            package.set_selected(false);
+        }
+        self.update_selection();
+        let numbers: Vec<String> = packages
+            .iter()
+            .filter(|package| self.store.find(&package.number()).is_some())
+            .map(Package::number)
+            .collect();
+        self.store.delete_with_undo(numbers, &self.toast_overlay, || {});
+    }
+
+    fn archive_packages(&self, packages: Vec<Package>) {
+        let packages: Vec<Package> = packages
+            .into_iter()
+            .filter(|package| {
+                update_package_metadata(&package.number(), |meta| meta.archived = true).is_ok()
+            })
+            .collect();
+        if packages.is_empty() {
+            return;
+        }
+        for package in &packages {
+            package.set_selected(false);
+            self.store.remove(package);
+        }
+        self.update_selection();
+
+        let title = match packages.len() {
+            1 => "Package archived".to_string(),
+            count => format!("{} packages archived", count),
+        };
+        let toast = Toast::builder()
+            .title(&title)
+            .button_label("Undo")
+            .timeout(5)
+            .build();
+
+        let store = self.store.clone();
+        toast.connect_button_clicked(move |_| {
+            for package in &packages {
+                if update_package_metadata(&package.number(), |meta| meta.archived = false).is_ok() {
+                    store.append(package);
+                }
             }
-        } else if info.events.is_empty() {
-            "No tracking data available".to_string()
-        } else {
-            info.label.clone()
+        });
+
+        self.toast_overlay.add_toast(toast);
+    }
+
+    fn refresh_packages(&self, packages: Vec<Package>) {
+        let store = self.store.clone();
+        glib::spawn_future_local(async move {
+            store.refresh(&packages).await;
+        });
+    }
+
+    fn copy_numbers(&self, packages: &[Package]) {
+        let numbers: Vec<String> = packages.iter().map(Package::number).collect();
+        self.frame.clipboard().set_text(&numbers.join("\n"));
+
+        let title = match numbers.len() {
+            1 => "Tracking number copied".to_string(),
+            count => format!("{} tracking numbers copied", count),
         };
+        self.toast_overlay.add_toast(Toast::new(&title));
+    }
+
+    fn export_packages(&self, packages: Vec<Package>) {
+        let parent = self.frame.root().and_downcast::<Window>();
+        let dialog = FileChooserNative::new(
+            Some("Export Packages"),
+            parent.as_ref(),
+            FileChooserAction::Save,
+            Some("Export"),
+            Some("Cancel"),
+        );
+        dialog.set_current_name("packages.csv");
+
+        // Native dialogs are not kept alive by GTK, hold on to it until it answers.
+        let dialog_holder = RefCell::new(Some(dialog.clone()));
+        let toast_overlay = self.toast_overlay.clone();
+        dialog.connect_response(move |dialog, response| {
+            dialog_holder.take();
+            if response != ResponseType::Accept {
+                return;
+            }
+            let Some(path) = dialog.file().and_then(|file| file.path()) else {
+                return;
+            };
+            let title = match fs::write(&path, packages_to_csv(&packages)) {
+                Ok(()) => format!("Exported {} packages", packages.len()),
+                Err(e) => format!("Export failed: {}", e),
+            };
+            toast_overlay.add_toast(Toast::new(&title));
+        });
+        dialog.show();
+    }
 
-        let package = ActionRow::builder()
-            .title(&info.id_ship)
-            .subtitle(&subtitle)
+    fn create_package_row(&self, package: &Package) -> ActionRow {
+        let row = ActionRow::builder()
+            .use_markup(false)
             .activatable(true)
             .build();
+        package
+            .bind_property("title", &row, "title")
+            .sync_create()
+            .build();
+        package
+            .bind_property("subtitle", &row, "subtitle")
+            .sync_create()
+            .build();
+
+        let rename_action = gio::SimpleAction::new("rename", None);
+        let package_clone = package.clone();
+        let row_clone = row.clone();
+        rename_action.connect_activate(move |_, _| {
+            handle_rename_package(&package_clone, &row_clone);
+        });
+
+        let archive_action = gio::SimpleAction::new("archive", None);
+        let package_clone = package.clone();
+        let list = self.clone();
+        archive_action.connect_activate(move |_, _| {
+            list.archive_packages(vec![package_clone.clone()]);
+        });
+
+        let refresh_action = gio::SimpleAction::new("refresh", None);
+        package
+            .bind_property("loading", &refresh_action, "enabled")
+            .invert_boolean()
+            .sync_create()
+            .build();
+        let package_clone = package.clone();
+        let store = self.store.clone();
+        refresh_action.connect_activate(move |_, _| {
+            let package = package_clone.clone();
+            let store = store.clone();
+            glib::spawn_future_local(async move {
+                store.refresh(&[package]).await;
+            });
+        });
+
+        let mute_action =
+            gio::SimpleAction::new_stateful("mute", None, &package.muted().to_variant());
+        let package_clone = package.clone();
+        mute_action.connect_activate(move |action, _| {
+            let muted = !package_clone.muted();
+            package_clone.save_muted(muted);
+            action.set_state(&package_clone.muted().to_variant());
+        });
+
+        let row_actions = gio::SimpleActionGroup::new();
+        row_actions.add_action(&refresh_action);
+        row_actions.add_action(&rename_action);
+        row_actions.add_action(&mute_action);
+        row_actions.add_action(&archive_action);
+        row.insert_action_group("row", Some(&row_actions));
+
+        let refresh_item = gio::MenuItem::new(Some("Refresh"), Some("row.refresh"));
+        refresh_item.set_attribute_value("accel", Some(&"<Control>r".to_variant()));
+
+        let row_menu = gio::Menu::new();
+        row_menu.append_item(&refresh_item);
+        row_menu.append(Some("Rename…"), Some("row.rename"));
+        row_menu.append(Some("Mute Notifications"), Some("row.mute"));
+        row_menu.append(Some("Archive"), Some("row.archive"));
+
+        let refresh_shortcut = ShortcutController::new();
+        refresh_shortcut.add_shortcut(Shortcut::new(
+            ShortcutTrigger::parse_string("<Control>r"),
+            Some(NamedAction::new("row.refresh")),
+        ));
+        row.add_controller(refresh_shortcut);
+
+        let menu_btn = MenuButton::builder()
+            .icon_name("view-more-symbolic")
+            .tooltip_markup("More options")
+            .menu_model(&row_menu)
+            .valign(Align::Center)
+            .build();
 
         let delete_btn = ToggleButton::builder()
             .icon_name("user-trash-symbolic")
@@ -160,60 +437,433 @@ async fn create_package_rows(
             .valign(Align::Center)
             .build();
 
-        let package_clone = package.clone();
-        let frame_clone = frame.clone();
-        let no_title_clone = no_package_title.clone();
+        let context_click = GestureClick::builder().button(BUTTON_SECONDARY).build();
+        let menu_btn_clone = menu_btn.clone();
+        context_click.connect_pressed(move |gesture, _, _, _| {
+            gesture.set_state(EventSequenceState::Claimed);
+            menu_btn_clone.popup();
+        });
+        row.add_controller(context_click);
 
+        let package_clone = package.clone();
+        let list = self.clone();
         delete_btn.connect_clicked(move |_| {
-            let delete_dialog =
-                handle_delete_numbers(&package_clone, &frame_clone, &no_title_clone);
-            delete_dialog.present(Some(&package_clone));
+            list.delete_packages(vec![package_clone.clone()]);
         });
 
-        let nav_view_clone = nav_view.clone();
-        let nav_page_clone = create_details_page(&info);
-        package.connect_activated(move |_| {
-            nav_view_clone.push(&nav_page_clone);
+        let check = CheckButton::builder().valign(Align::Center).build();
+        package
+            .bind_property("selected", &check, "active")
+            .bidirectional()
+            .sync_create()
+            .build();
+        let list = self.clone();
+        check.connect_toggled(move |_| list.update_selection());
+
+        self.selection_button
+            .bind_property("active", &check, "visible")
+            .sync_create()
+            .build();
+        for button in [menu_btn.upcast_ref::<Widget>(), delete_btn.upcast_ref()] {
+            self.selection_button
+                .bind_property("active", button, "visible")
+                .invert_boolean()
+                .sync_create()
+                .build();
+        }
+
+        let list = self.clone();
+        let package_clone = package.clone();
+        row.connect_activated(move |_| {
+            if list.selection_button.is_active() {
+                package_clone.set_selected(!package_clone.selected());
+            } else {
+                list.nav_view
+                    .push(&create_details_page(&package_clone, &list.store));
+            }
         });
 
-        package.add_suffix(&delete_btn);
-        list.append(&package);
+        let spinner = Spinner::builder().valign(Align::Center).build();
+        package
+            .bind_property("loading", &spinner, "visible")
+            .sync_create()
+            .build();
+
+        let unseen_label = Label::builder()
+            .label("New")
+            .tooltip_markup("Reached a new milestone since you last looked")
+            .css_classes(vec!["accent", "caption-heading"])
+            .valign(Align::Center)
+            .build();
+        package
+            .bind_property("unseen", &unseen_label, "visible")
+            .sync_create()
+            .build();
+
+        let status_pill = create_bound_status_pill(package);
+        package
+            .bind_property("loading", &status_pill, "visible")
+            .invert_boolean()
+            .sync_create()
+            .build();
+
+        row.add_prefix(&check);
+        row.add_prefix(&create_bound_status_icon(package));
+        row.add_suffix(&spinner);
+        row.add_suffix(&unseen_label);
+        row.add_suffix(&status_pill);
+        row.add_suffix(&menu_btn);
+        row.add_suffix(&delete_btn);
+        row
+    }
+
+    async fn load(&self, input: &str) {
+        self.store.add_numbers(input);
+        let packages: Vec<Package> = input
+            .lines()
+            .map(normalize)
+            .filter_map(|number| self.store.find(&number))
+            .collect();
+        self.store.refresh(&packages).await;
     }
-    if list.first_child().is_none() {
-        frame.set_child(Some(no_package_title));
+
+    async fn refresh(&self, refresh_button: Button) {
+        self.store.refresh_all().await;
+        refresh_button.set_sensitive(true);
     }
 }
 
-async fn refresh_tracking_info(
-    _package_rows: &ListBox,
-    nav_view: &NavigationView,
-    frame: &Frame,
-    no_package_title: &StatusPage,
-    refresh_button: Button,
-) {
-    let numbers = load_tracking_numbers();
-
-    if !numbers.is_empty() {
-        frame.set_child(Some(&show_loading_state()));
-        let input = numbers.join("\n");
-        create_package_rows(&input, nav_view, frame, no_package_title).await;
-    } else {
-        frame.set_child(Some(no_package_title));
+fn handle_rename_package(package: &Package, parent: &ActionRow) {
+    let nickname_entry = Entry::builder()
+        .placeholder_text("Nickname")
+        .text(package.nickname().unwrap_or_default())
+        .activates_default(true)
+        .build();
+
+    let rename_dialog = AlertDialog::builder()
+        .heading("Rename Package")
+        .body(package.number())
+        .close_response("cancel")
+        .default_response("rename")
+        .extra_child(&nickname_entry)
+        .build();
+
+    rename_dialog.add_response("cancel", "Cancel");
+    rename_dialog.add_response("rename", "Rename");
+    rename_dialog.set_response_appearance("rename", ResponseAppearance::Suggested);
+
+    let package_clone = package.clone();
+    rename_dialog.connect_response(None, move |_, response| {
+        if response == "rename" {
+            let nickname = nickname_entry.text().trim().to_string();
+            package_clone.save_nickname((!nickname.is_empty()).then_some(nickname));
+        }
+    });
+
+    rename_dialog.present(Some(parent));
+}
+
+fn create_search_bar(list: &PackageList) -> (SearchBar, SearchEntry) {
+    let search_entry = SearchEntry::builder()
+        .placeholder_text("Search by number, nickname, note, courier or event")
+        .hexpand(true)
+        .build();
+
+    let list_clone = list.clone();
+    search_entry.connect_search_changed(move |entry| {
+        list_clone.filter.borrow_mut().query = entry.text().to_string();
+        list_clone.invalidate_filter();
+    });
+
+    let chips = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .halign(Align::Center)
+        .build();
+
+    for section in Section::ALL {
+        let chip = ToggleButton::builder()
+            .label(section.title())
+            .css_classes(vec!["pill"])
+            .build();
+
+        let list_clone = list.clone();
+        chip.connect_toggled(move |chip| {
+            {
+                let mut filter = list_clone.filter.borrow_mut();
+                if chip.is_active() {
+                    filter.sections.insert(section);
+                } else {
+                    filter.sections.remove(&section);
+                }
+            }
+            list_clone.invalidate_filter();
+        });
+        chips.append(&chip);
     }
-    refresh_button.set_sensitive(true);
+
+    let search_box = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(10)
+        .build();
+    search_box.append(&search_entry);
+    search_box.append(&chips);
+
+    let search_bar = SearchBar::builder()
+        .child(&search_box)
+        .show_close_button(true)
+        .margin_bottom(20)
+        .build();
+    search_bar.connect_entry(&search_entry);
+
+    search_bar.connect_search_mode_enabled_notify(move |search_bar| {
+        if search_bar.is_search_mode() {
+            return;
+        }
+        let mut chip_opt = chips.first_child();
+        while let Some(chip) = chip_opt {
+            if let Some(chip) = chip.downcast_ref::<ToggleButton>() {
+                chip.set_active(false);
+            }
+            chip_opt = chip.next_sibling();
+        }
+    });
+
+    (search_bar, search_entry)
 }
 
-pub fn create_tracking_area(text_field: TextView, nav_view: NavigationView) -> (Button, Box) {
-    let text_field_cloned = text_field.clone();
-    let package_rows = ListBox::builder().css_classes(vec!["boxed-list"]).build();
+fn create_sort_button(list: &PackageList) -> (MenuButton, gio::SimpleActionGroup) {
+    let settings = load_settings();
+
+    let sort_by_action = gio::SimpleAction::new_stateful(
+        "sort-by",
+        Some(glib::VariantTy::STRING),
+        &settings.sort_key.key().to_variant(),
+    );
+    let list_clone = list.clone();
+    sort_by_action.connect_change_state(move |action, value| {
+        let Some(sort_key) = value
+            .and_then(|value| value.str())
+            .and_then(SortKey::from_key)
+        else {
+            return;
+        };
+        action.set_state(&sort_key.key().to_variant());
+        let mut settings = load_settings();
+        settings.sort_key = sort_key;
+        let _ = save_settings(&settings);
+        list_clone.sort_order.set((sort_key, settings.sort_descending));
+        list_clone.invalidate_sort();
+    });
 
-    let track_button = Button::builder()
-        .label("Track")
-        .width_request(200)
+    let descending_action = gio::SimpleAction::new_stateful(
+        "sort-descending",
+        None,
+        &settings.sort_descending.to_variant(),
+    );
+    let list_clone = list.clone();
+    descending_action.connect_change_state(move |action, value| {
+        let Some(descending) = value.and_then(|value| value.get::<bool>()) else {
+            return;
+        };
+        action.set_state(&descending.to_variant());
+        let mut settings = load_settings();
+        settings.sort_descending = descending;
+        let _ = save_settings(&settings);
+        list_clone.sort_order.set((settings.sort_key, descending));
+        list_clone.invalidate_sort();
+    });
+
+    let list_actions = gio::SimpleActionGroup::new();
+    list_actions.add_action(&sort_by_action);
+    list_actions.add_action(&descending_action);
+
+    let sort_keys_menu = gio::Menu::new();
+    for sort_key in SortKey::ALL {
+        sort_keys_menu.append(
+            Some(sort_key.label()),
+            Some(&format!("list.sort-by::{}", sort_key.key())),
+        );
+    }
+    let direction_menu = gio::Menu::new();
+    direction_menu.append(Some("Descending"), Some("list.sort-descending"));
+
+    let sort_menu = gio::Menu::new();
+    sort_menu.append_section(Some("Sort By"), &sort_keys_menu);
+    sort_menu.append_section(None, &direction_menu);
+
+    let sort_button = MenuButton::builder()
+        .icon_name("view-sort-ascending-symbolic")
+        .tooltip_markup("Sort packages")
+        .menu_model(&sort_menu)
+        .width_request(40)
         .height_request(25)
-        .halign(Align::Center)
-        .css_classes(vec!["suggested-action", "pill"])
+        .valign(Align::End)
+        .build();
+
+    (sort_button, list_actions)
+}
+
+fn track_input(list: &PackageList, input: String) {
+    let list = list.clone();
+    glib::spawn_future_local(async move {
+        list.load(&input).await;
+    });
+}
+
+/// Saves the carriers named by tracking links as courier codes for the Ship24 lookups.
+fn save_courier_hints(carriers: &[(String, Carrier)]) {
+    for (number, carrier) in carriers {
+        if let Some(courier_code) = carrier.ship24_code(number) {
+            let _ = update_package_metadata(number, |meta| {
+                meta.courier_code = Some(courier_code.to_string())
+            });
+        }
+    }
+}
+
+/// Adds the number or link typed in the header entry on its own, fetching only that package.
+fn track_quick_add(list: &PackageList, entry: &Entry) {
+    let text = entry.text().trim().to_string();
+    if text.is_empty() {
+        return;
+    }
+    if let Validation::Invalid(carrier) = validate_input_line(&text) {
+        let title = format!("Not a valid {} tracking number", carrier.name());
+        list.toast_overlay.add_toast(Toast::new(&title));
+        return;
+    }
+
+    let resolved = resolve_urls(&text);
+    if !resolved.unrecognized.is_empty() {
+        list.toast_overlay
+            .add_toast(Toast::new("Unrecognized tracking link"));
+        return;
+    }
+    save_courier_hints(&resolved.carriers);
+
+    let number = normalize(&resolved.text);
+    entry.set_text("");
+    if list.store.find(&number).is_some() {
+        let title = format!("{} is already tracked", number);
+        list.toast_overlay.add_toast(Toast::new(&title));
+        return;
+    }
+    track_input(list, number);
+}
+
+fn create_selection_bar(list: &PackageList) -> ActionBar {
+    let select_all_button = Button::builder()
+        .label("Select All")
         .build();
+    let list_clone = list.clone();
+    select_all_button.connect_clicked(move |_| list_clone.select_all());
+
+    let refresh_button = Button::builder()
+        .icon_name("view-refresh-symbolic")
+        .tooltip_markup("Refresh selected packages")
+        .build();
+    let list_clone = list.clone();
+    refresh_button.connect_clicked(move |_| {
+        list_clone.refresh_packages(list_clone.selected_packages());
+    });
+
+    let copy_button = Button::builder()
+        .icon_name("edit-copy-symbolic")
+        .tooltip_markup("Copy tracking numbers")
+        .build();
+    let list_clone = list.clone();
+    copy_button.connect_clicked(move |_| {
+        list_clone.copy_numbers(&list_clone.selected_packages());
+    });
+
+    let export_button = Button::builder()
+        .icon_name("document-save-symbolic")
+        .tooltip_markup("Export selected packages as CSV")
+        .build();
+    let list_clone = list.clone();
+    export_button.connect_clicked(move |_| {
+        list_clone.export_packages(list_clone.selected_packages());
+    });
+
+    let archive_button = Button::builder()
+        .icon_name("folder-symbolic")
+        .tooltip_markup("Archive selected packages")
+        .build();
+    let list_clone = list.clone();
+    archive_button.connect_clicked(move |_| {
+        list_clone.archive_packages(list_clone.selected_packages());
+    });
+
+    let delete_button = Button::builder()
+        .icon_name("user-trash-symbolic")
+        .tooltip_markup("Delete selected packages")
+        .css_classes(vec!["destructive-action"])
+        .build();
+    let list_clone = list.clone();
+    delete_button.connect_clicked(move |_| {
+        list_clone.delete_packages(list_clone.selected_packages());
+    });
+
+    list.selection_actions.append(&refresh_button);
+    list.selection_actions.append(&copy_button);
+    list.selection_actions.append(&export_button);
+    list.selection_actions.append(&archive_button);
+    list.selection_actions.append(&delete_button);
+
+    let selection_bar = ActionBar::builder()
+        .margin_top(10)
+        .build();
+    selection_bar.pack_start(&select_all_button);
+    selection_bar.set_center_widget(Some(&list.selection_count));
+    selection_bar.pack_end(&list.selection_actions);
+
+    list.selection_button
+        .bind_property("active", &selection_bar, "revealed")
+        .sync_create()
+        .build();
+
+    selection_bar
+}
+
+/// The home page widgets built around the package list, placed by the window.
+pub struct TrackingArea {
+    pub quick_add: Box,
+    pub add_buttons: Box,
+    pub package_area: Box,
+    /// Imports what is dropped onto the home page.
+    pub drop_target: DropTarget,
+    /// Tracks the number or link given as its string target, for the window to register.
+    pub track_action: gio::SimpleAction,
+    /// Opens the search with Ctrl+F while the focus is on the home page.
+    pub search_shortcut: ShortcutController,
+}
+
+pub fn create_tracking_area(
+    store: &PackageStore,
+    banner: Banner,
+    nav_view: NavigationView,
+    toast_overlay: ToastOverlay,
+) -> TrackingArea {
+    let (quick_add, quick_add_entry) = create_quick_add_entry();
+    let (bulk_add_dialog, text_field, track_button) = create_bulk_add_dialog();
+
+    let bulk_add_button = Button::builder()
+        .icon_name("edit-paste-symbolic")
+        .tooltip_markup("Add several tracking numbers")
+        .build();
+
+    let import_button = Button::builder()
+        .icon_name("document-open-symbolic")
+        .tooltip_markup("Import from label images, PDFs, emails or CSV files")
+        .build();
+
+    let add_buttons = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    add_buttons.append(&bulk_add_button);
+    add_buttons.append(&import_button);
 
     let title_container = Box::builder()
         .orientation(Orientation::Horizontal)
@@ -229,6 +879,14 @@ pub fn create_tracking_area(text_field: TextView, nav_view: NavigationView) -> (
         .halign(Align::Start)
         .build();
 
+    let search_button = ToggleButton::builder()
+        .icon_name("system-search-symbolic")
+        .tooltip_markup("Search packages")
+        .width_request(40)
+        .height_request(25)
+        .valign(Align::End)
+        .build();
+
     let refresh_button = Button::builder()
         .icon_name("view-refresh-symbolic")
         .tooltip_markup("Refresh tracking information")
@@ -237,93 +895,110 @@ pub fn create_tracking_area(text_field: TextView, nav_view: NavigationView) -> (
         .valign(Align::End)
         .build();
 
-    let no_package_title = StatusPage::builder()
-        .title("No tracked packages")
-        .description("Enter one or more tracking numbers.")
-        .icon_name("system-search-symbolic")
-        .height_request(440)
-        .build();
-
-    let _scrolled_window = ScrolledWindow::builder()
-        .child(&package_rows)
-        .height_request(440)
-        .vexpand(false)
-        .build();
-
-    let frame = Frame::builder()
-        .child(&no_package_title)
-        .css_classes(vec!["boxed-list"])
+    let archive_button = Button::builder()
+        .icon_name("folder-symbolic")
+        .tooltip_markup("Show archived packages")
+        .width_request(40)
+        .height_request(25)
+        .valign(Align::End)
         .build();
 
-    let nav_view_clone = nav_view.clone();
-    let frame_clone = frame.clone();
-    let no_package_title_clone = no_package_title.clone();
-
-    glib::spawn_future_local(async move {
-        let saved_numbers = load_tracking_numbers();
-        if !saved_numbers.is_empty() {
-            let input = saved_numbers.join("\n");
-            create_package_rows(
-                &input,
-                &nav_view_clone,
-                &frame_clone,
-                &no_package_title_clone,
-            )
-            .await;
-        }
-    });
-
-    let package_rows_for_refresh = package_rows.clone();
-    let frame_for_refresh = frame.clone();
-    let nav_view_for_refresh = nav_view.clone();
-    let no_package_title_for_refresh = no_package_title.clone();
+    let package_list = PackageList::new(store.clone(), nav_view.clone(), toast_overlay.clone());
 
+    let list_for_refresh = package_list.clone();
     refresh_button.connect_clicked(move |button| {
         button.set_sensitive(false);
-        let package_rows = package_rows_for_refresh.clone();
-        let nav_view = nav_view_for_refresh.clone();
-        let frame = frame_for_refresh.clone();
-        let no_package_title = no_package_title_for_refresh.clone();
+        let list = list_for_refresh.clone();
         let button_clone = button.clone();
 
         glib::spawn_future_local(async move {
-            refresh_tracking_info(
-                &package_rows,
-                &nav_view,
-                &frame,
-                &no_package_title,
-                button_clone,
-            )
-            .await;
+            list.refresh(button_clone).await;
         });
     });
 
+    let nav_view_for_archive = nav_view.clone();
+    let toast_overlay_for_archive = toast_overlay.clone();
+    let store_for_archive = package_list.store.clone();
+
+    archive_button.connect_clicked(move |_| {
+        let archive_page = create_archive_page(
+            &nav_view_for_archive,
+            &toast_overlay_for_archive,
+            &store_for_archive,
+        );
+        nav_view_for_archive.push(&archive_page);
+    });
+
+    let (search_bar, search_entry) = create_search_bar(&package_list);
+    search_button
+        .bind_property("active", &search_bar, "search-mode-enabled")
+        .bidirectional()
+        .sync_create()
+        .build();
+
     let package_area = Box::builder()
         .orientation(Orientation::Vertical)
-        .margin_top(70)
+        .width_request(600)
         .build();
 
-    let _package_rows_cloned = package_rows.clone();
-    let frame_cloned = frame.clone();
-    track_button.connect_clicked(move |button| {
-        button.set_sensitive(false);
-        let tf_buff = text_field_cloned.buffer();
+    let (sort_button, list_actions) = create_sort_button(&package_list);
+    package_area.insert_action_group("list", Some(&list_actions));
+
+    let search_bar_clone = search_bar.clone();
+    let search_shortcut = ShortcutController::new();
+    search_shortcut.add_shortcut(Shortcut::new(
+        ShortcutTrigger::parse_string("<Control>f"),
+        Some(CallbackAction::new(move |_, _| {
+            search_bar_clone.set_search_mode(true);
+            search_entry.grab_focus();
+            glib::Propagation::Stop
+        })),
+    ));
+
+    let list_for_quick_add = package_list.clone();
+    quick_add_entry.connect_activate(move |entry| {
+        track_quick_add(&list_for_quick_add, entry);
+    });
+
+    let dialog_clone = bulk_add_dialog.clone();
+    bulk_add_button.connect_clicked(move |button| {
+        dialog_clone.present(Some(button));
+    });
+
+    let list_for_track = package_list.clone();
+    track_button.connect_clicked(move |_| {
+        let tf_buff = text_field.buffer();
         let text = tf_buff.text(&tf_buff.start_iter(), &tf_buff.end_iter(), false);
-        let nav_view = nav_view.clone();
-        let frame_cloned = frame_cloned.clone();
-        let no_package_title = no_package_title.clone();
-        let button_clone = button.clone();
+        bulk_add_dialog.close();
+        track_text(&list_for_track, &text, "the pasted text");
+        tf_buff.set_text("");
+    });
 
-        glib::spawn_future_local(async move {
-            create_package_rows(&text, &nav_view, &frame_cloned, &no_package_title).await;
-            button_clone.set_sensitive(true);
-        });
+    let list_for_import = package_list.clone();
+    import_button.connect_clicked(move |_| {
+        open_import_files(&list_for_import);
     });
 
+    watch_clipboard(&package_list, &banner);
+    watch_mailbox(&package_list);
+
     title_container.append(&tracked_package_title);
+    title_container.append(&search_button);
+    title_container.append(&package_list.selection_button);
+    title_container.append(&sort_button);
+    title_container.append(&archive_button);
     title_container.append(&refresh_button);
     package_area.append(&title_container);
-    package_area.append(&frame);
-
-    return (track_button, package_area);
+    package_area.append(&search_bar);
+    package_area.append(&package_list.frame);
+    package_area.append(&create_selection_bar(&package_list));
+
+    TrackingArea {
+        quick_add,
+        add_buttons,
+        package_area,
+        drop_target: create_drop_target(&package_list),
+        track_action: create_track_action(&package_list),
+        search_shortcut,
+    }
 }
@@ -0,0 +1,268 @@
+use super::{save_courier_hints, track_input, PackageList};
+use crate::barcode::{decode_file, is_barcode_file, tracking_candidates};
+use crate::email::{email_packages, is_mbox};
+use crate::home_page::candidates_dialog::create_candidates_dialog;
+use crate::home_page::csv_import::parse_packages_csv;
+use crate::storage::{load_tracking_numbers, update_package_metadata};
+use crate::tracking_number::extract::{extract_tracking_numbers, is_number_list, Candidate};
+use crate::tracking_number::url::resolve_urls;
+use crate::tracking_number::validate::{validate_input_line, Validation};
+use adw::{
+    gio, glib,
+    gtk::{gdk, DropTarget, FileChooserAction, FileChooserNative, FileFilter, ResponseType, Window},
+    prelude::*,
+    Toast,
+};
+use std::cell::RefCell;
+use std::fs;
+use std::path::PathBuf;
+
+/// Tracks text holding one number or link per line, or else offers the numbers found in it.
+pub fn track_text(list: &PackageList, text: &str, source: &str) {
+    if !is_number_list(text) {
+        offer_candidates(
+            list,
+            &extract_tracking_numbers(text),
+            source,
+            &format!("No tracking numbers found in {}", source),
+        );
+        return;
+    }
+
+    let resolved = resolve_urls(text);
+    save_courier_hints(&resolved.carriers);
+    if !resolved.unrecognized.is_empty() {
+        let title = match resolved.unrecognized.as_slice() {
+            [url] => format!("Unrecognized tracking link: {}", url),
+            urls => format!("{} tracking links were not recognized", urls.len()),
+        };
+        list.toast_overlay.add_toast(Toast::new(&title));
+    }
+    let text = resolved.text;
+
+    let (valid, invalid): (Vec<&str>, Vec<&str>) = text
+        .lines()
+        .partition(|line| !matches!(validate_input_line(line), Validation::Invalid(_)));
+    if !invalid.is_empty() {
+        let title = match invalid.len() {
+            1 => "Skipped 1 invalid tracking number".to_string(),
+            count => format!("Skipped {} invalid tracking numbers", count),
+        };
+        list.toast_overlay.add_toast(Toast::new(&title));
+    }
+    let skipped_lines = !invalid.is_empty() || !resolved.unrecognized.is_empty();
+    if skipped_lines && valid.iter().all(|line| line.trim().is_empty()) {
+        return;
+    }
+    track_input(list, valid.join("\n"));
+}
+
+/// Asks which of the found tracking numbers to add, or tells the user none were found.
+fn offer_candidates(
+    list: &PackageList,
+    candidates: &[Candidate],
+    source: &str,
+    empty_message: &str,
+) {
+    if candidates.is_empty() {
+        list.toast_overlay.add_toast(Toast::new(empty_message));
+        return;
+    }
+
+    let list_clone = list.clone();
+    let dialog = create_candidates_dialog(candidates, source, move |numbers| {
+        track_input(&list_clone, numbers.join("\n"));
+    });
+    dialog.present(Some(&list.frame));
+}
+
+/// Reads the tracking barcodes of label images and PDFs off the main thread, then offers their numbers.
+fn scan_barcode_files(list: &PackageList, paths: Vec<PathBuf>) {
+    let list = list.clone();
+    glib::spawn_future_local(async move {
+        let results = gio::spawn_blocking(move || {
+            paths.iter().map(|path| decode_file(path)).collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_default();
+
+        let mut texts = Vec::new();
+        for result in results {
+            match result {
+                Ok(decoded) => texts.extend(decoded),
+                Err(e) => list.toast_overlay.add_toast(Toast::new(&e.to_string())),
+            }
+        }
+        offer_candidates(
+            &list,
+            &tracking_candidates(&texts),
+            "the scanned barcodes",
+            "No tracking barcodes found",
+        );
+    });
+}
+
+/// Adds the packages of a CSV file, keeping the nicknames and notes of the ones not tracked yet.
+fn import_csv(list: &PackageList, text: &str) {
+    let packages = parse_packages_csv(text);
+    if packages.is_empty() {
+        list.toast_overlay
+            .add_toast(Toast::new("No tracking numbers found in the CSV file"));
+        return;
+    }
+
+    let tracked = load_tracking_numbers();
+    for package in packages.iter().filter(|package| !tracked.contains(&package.number)) {
+        let _ = update_package_metadata(&package.number, |meta| {
+            meta.nickname = package.nickname.clone();
+            meta.notes = package.notes.clone().unwrap_or_default();
+        });
+    }
+    let numbers: Vec<&str> = packages.iter().map(|package| package.number.as_str()).collect();
+    track_input(list, numbers.join("\n"));
+}
+
+/// Offers the tracking numbers found in an .eml or mbox file, naming the new packages after the
+/// email subject and noting the merchant and order.
+fn import_emails(list: &PackageList, raw: Vec<u8>, name: String) {
+    let list = list.clone();
+    glib::spawn_future_local(async move {
+        let packages = gio::spawn_blocking(move || email_packages(&raw))
+            .await
+            .unwrap_or_default();
+        if packages.is_empty() {
+            let title = format!("No tracking numbers found in {}", name);
+            list.toast_overlay.add_toast(Toast::new(&title));
+            return;
+        }
+
+        let candidates: Vec<Candidate> = packages
+            .iter()
+            .map(|package| package.candidate.clone())
+            .collect();
+        let list_clone = list.clone();
+        let dialog = create_candidates_dialog(&candidates, &name, move |numbers| {
+            let tracked = load_tracking_numbers();
+            for package in packages.iter().filter(|package| {
+                numbers.contains(&package.candidate.number)
+                    && !tracked.contains(&package.candidate.number)
+            }) {
+                let _ = update_package_metadata(&package.candidate.number, |meta| {
+                    meta.nickname = package.subject.clone();
+                    meta.notes = package.notes();
+                });
+            }
+            track_input(&list_clone, numbers.join("\n"));
+        });
+        dialog.present(Some(&list.frame));
+    });
+}
+
+/// Routes dropped or opened files to the parser for their type: barcode scanning for images
+/// and PDFs, CSV import, email import, or number extraction for any other text.
+fn import_files(list: &PackageList, paths: Vec<PathBuf>) {
+    let (barcode_files, paths): (Vec<PathBuf>, Vec<PathBuf>) =
+        paths.into_iter().partition(|path| is_barcode_file(path));
+    if !barcode_files.is_empty() {
+        scan_barcode_files(list, barcode_files);
+    }
+
+    for path in paths {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                let title = format!("Could not read {}: {}", name, e);
+                list.toast_overlay.add_toast(Toast::new(&title));
+                continue;
+            }
+        };
+
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "csv" => import_csv(list, &String::from_utf8_lossy(&bytes)),
+            "eml" | "mbox" => import_emails(list, bytes, name),
+            _ if is_mbox(&bytes) => import_emails(list, bytes, name),
+            _ => track_text(list, &String::from_utf8_lossy(&bytes), &name),
+        }
+    }
+}
+
+pub fn open_import_files(list: &PackageList) {
+    let parent = list.frame.root().and_downcast::<Window>();
+    let dialog = FileChooserNative::new(
+        Some("Import Tracking Numbers"),
+        parent.as_ref(),
+        FileChooserAction::Open,
+        Some("Import"),
+        Some("Cancel"),
+    );
+    dialog.set_select_multiple(true);
+
+    let filter = FileFilter::new();
+    filter.set_name(Some("Labels, Emails and CSV Files"));
+    filter.add_pixbuf_formats();
+    filter.add_mime_type("application/pdf");
+    filter.add_mime_type("message/rfc822");
+    filter.add_mime_type("application/mbox");
+    filter.add_mime_type("text/csv");
+    filter.add_suffix("eml");
+    filter.add_suffix("mbox");
+    filter.add_suffix("csv");
+    dialog.add_filter(&filter);
+
+    // Native dialogs are not kept alive by GTK, hold on to it until it answers.
+    let dialog_holder = RefCell::new(Some(dialog.clone()));
+    let list = list.clone();
+    dialog.connect_response(move |dialog, response| {
+        dialog_holder.take();
+        if response != ResponseType::Accept {
+            return;
+        }
+        let paths = dialog
+            .files()
+            .iter::<gio::File>()
+            .filter_map(Result::ok)
+            .filter_map(|file| file.path())
+            .collect();
+        import_files(&list, paths);
+    });
+    dialog.show();
+}
+
+pub fn create_track_action(list: &PackageList) -> gio::SimpleAction {
+    let action = gio::SimpleAction::new("track-number", Some(glib::VariantTy::STRING));
+    let list = list.clone();
+    action.connect_activate(move |_, target| {
+        if let Some(text) = target.and_then(|target| target.get::<String>()) {
+            track_text(&list, &text, "the link");
+        }
+    });
+    action
+}
+
+pub fn create_drop_target(list: &PackageList) -> DropTarget {
+    let drop_target = DropTarget::new(glib::Type::INVALID, gdk::DragAction::COPY);
+    drop_target.set_types(&[gdk::FileList::static_type(), String::static_type()]);
+
+    let list = list.clone();
+    drop_target.connect_drop(move |_, value, _, _| {
+        if let Ok(files) = value.get::<gdk::FileList>() {
+            let paths = files.files().iter().filter_map(|file| file.path()).collect();
+            import_files(&list, paths);
+            return true;
+        }
+        if let Ok(text) = value.get::<String>() {
+            track_text(&list, &text, "the dropped text");
+            return true;
+        }
+        false
+    });
+    drop_target
+}
@@ -0,0 +1,119 @@
+use super::{track_input, PackageList};
+use crate::email::check_mailbox;
+use crate::package::Package;
+use crate::storage::{load_settings, load_tracking_numbers, update_package_metadata};
+use crate::tracking_number::detect_carrier;
+use crate::tracking_number::normalize::normalize;
+use adw::{glib, gtk::Window, prelude::*, Banner, Toast};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Reveals `banner` while the clipboard holds a valid tracking number that is not tracked yet.
+pub fn watch_clipboard(list: &PackageList, banner: &Banner) {
+    let offered = Rc::new(RefCell::new(String::new()));
+
+    let list_clone = list.clone();
+    let offered_clone = offered.clone();
+    banner.connect_button_clicked(move |banner| {
+        banner.set_revealed(false);
+        track_input(&list_clone, offered_clone.take());
+    });
+
+    let check_clipboard = move |banner: &Banner| {
+        let banner = banner.clone();
+        let offered = offered.clone();
+        glib::spawn_future_local(async move {
+            let text = banner
+                .clipboard()
+                .read_text_future()
+                .await
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+            let number = normalize(text.trim());
+            let trackable = !text.trim().contains('\n')
+                && detect_carrier(&number).is_some()
+                && !load_tracking_numbers().contains(&number);
+
+            if trackable {
+                banner.set_title(&format!("Track {} from the clipboard?", number));
+                offered.replace(number);
+            }
+            banner.set_revealed(trackable);
+        });
+    };
+
+    // The window only exists once the home page is realized; check each time it gains focus.
+    // The handler is dropped on unrealize so realizing the page again does not add another.
+    let focus_handler: Rc<RefCell<Option<(Window, glib::SignalHandlerId)>>> = Rc::new(RefCell::new(None));
+    let focus_handler_clone = focus_handler.clone();
+    banner.connect_realize(move |banner| {
+        let Some(window) = banner.root().and_downcast::<Window>() else {
+            return;
+        };
+        let banner = banner.clone();
+        let check_clipboard = check_clipboard.clone();
+        let handler = window.connect_is_active_notify(move |window| {
+            if window.is_active() {
+                check_clipboard(&banner);
+            }
+        });
+        focus_handler_clone.replace(Some((window, handler)));
+    });
+    banner.connect_unrealize(move |_| {
+        if let Some((window, handler)) = focus_handler.take() {
+            window.disconnect(handler);
+        }
+    });
+}
+
+/// Polls the configured mailbox while the watcher is enabled, adding the numbers of new emails.
+pub fn watch_mailbox(list: &PackageList) {
+    let list = list.clone();
+    glib::spawn_future_local(async move {
+        // Only a new failure is shown, not the same one on every check.
+        let mut last_error = None;
+        loop {
+            let settings = load_settings().mailbox;
+            if settings.enabled && !settings.host.is_empty() {
+                let mut numbers: Vec<String> = Vec::new();
+                let result = check_mailbox(&settings, |packages| {
+                    let tracked = load_tracking_numbers();
+                    for package in packages {
+                        let number = package.candidate.number.clone();
+                        if tracked.contains(&number) || numbers.contains(&number) {
+                            continue;
+                        }
+                        let _ = update_package_metadata(&number, |meta| {
+                            meta.nickname = package.sender_nickname();
+                            meta.notes = package.notes();
+                        });
+                        numbers.push(number);
+                    }
+                })
+                .await;
+                let error = result.err().map(|e| e.to_string());
+                if let Some(error) = error.as_ref().filter(|error| last_error.as_ref() != Some(*error)) {
+                    list.toast_overlay.add_toast(Toast::new(&format!("Failed to check mailbox: {}", error)));
+                }
+                last_error = error;
+
+                if !numbers.is_empty() {
+                    list.store.add_numbers(&numbers.join("\n"));
+                    let packages: Vec<Package> =
+                        numbers.iter().filter_map(|number| list.store.find(number)).collect();
+                    let title = match packages.len() {
+                        1 => "Added 1 package from your mailbox".to_string(),
+                        count => format!("Added {} packages from your mailbox", count),
+                    };
+                    list.toast_overlay.add_toast(Toast::new(&title));
+                    list.store.refresh(&packages).await;
+                }
+            }
+
+            // Check the settings again every minute while the watcher is off.
+            let minutes = if settings.enabled { settings.poll_minutes.max(1) } else { 1 };
+            glib::timeout_future_seconds(minutes * 60).await;
+        }
+    });
+}
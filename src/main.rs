@@ -1,81 +1,297 @@
-use adw::gtk::{glib, Application, Box, HeaderBar, ScrolledWindow, PolicyType, Orientation, Align};
-use adw::{gio, prelude::*, NavigationPage, NavigationView, WindowTitle, ApplicationWindow};
+use adw::gtk::{self as gtk, gdk, glib, Application, Box, CssProvider, HeaderBar, MenuButton, ScrolledWindow, PolicyType, Orientation, Align};
+use adw::{gio, prelude::*, Banner, NavigationPage, NavigationView, ApplicationWindow, ToastOverlay};
 
 mod api;
+mod background;
+mod barcode;
+mod cli;
 mod storage;
 mod home_page;
 mod details_page;
+mod email;
+mod archive_page;
+mod preferences;
+mod notifications;
+mod package;
+mod tracking_number;
 
-use home_page::tracking_input::create_input_area;
+use background::{apply_background_settings, BACKGROUND_OPTION};
+use details_page::details::create_details_page;
 use home_page::tracking_list::create_tracking_area;
+use package::scheduler::start_auto_refresh;
+use package::store::PackageStore;
+use preferences::create_preferences_dialog;
+use tracking_number::normalize::{normalize, percent_decode};
+
+const URI_SCHEME: &str = "package-tracker";
+const ADD_OPTION: &str = "add";
+const SHOW_OPTION: &str = "show";
 
 #[tokio::main]
 async fn main() -> glib::ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(exit_code) = cli::run(&args).await {
+        return exit_code;
+    }
+
     gio::resources_register_include!("simple_package_tracker.gresource")
         .expect("Failed to register embedded resources");
+    storage::migrate_saved_data();
+    let store = PackageStore::new();
 
     let app = Application::builder()
         .application_id("io.github.alrick.simple_package_tracker")
+        .flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE | gio::ApplicationFlags::HANDLES_OPEN)
         .build();
 
-    app.connect_startup(|_| {
+    let store_clone = store.clone();
+    app.connect_startup(move |app| {
         adw::init().expect("Failed to initialize libadwaita");
+
+        let provider = CssProvider::new();
+        provider.load_from_resource("/io/github/alrick/simple_package_tracker/style.css");
+        gtk::style_context_add_provider_for_display(
+            &gdk::Display::default().expect("Could not connect to a display"),
+            &provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+
+        let quit_action = gio::SimpleAction::new("quit", None);
+        let app_clone = app.clone();
+        quit_action.connect_activate(move |_, _| app_clone.quit());
+        app.add_action(&quit_action);
+        app.set_accels_for_action("app.quit", &["<Control>q"]);
+
+        // Targets of the buttons on status change notifications, of --show/--add and of links,
+        // which may arrive before the window exists.
+        add_window_action(app, &store_clone, "open-details");
+        add_window_action(app, &store_clone, "track-number");
+        add_mark_seen_action(app, &store_clone);
+
+        apply_background_settings();
+        start_auto_refresh(&store_clone);
     });
 
-    app.connect_activate(|app| {
-        let header = HeaderBar::builder()
-            .title_widget(&WindowTitle::new("Simple Package Tracker", ""))
-            .show_title_buttons(true)
-            .build();
+    let store_clone = store.clone();
+    app.connect_shutdown(move |_| store_clone.commit_pending_deletions());
 
-        let nav_view = NavigationView::new();
-        let (tracking_input_window, text_field) = create_input_area();
-        let (track_button, package_rows) = create_tracking_area(text_field, nav_view.clone());
-        
-        let content = Box::builder()
-            .orientation(Orientation::Vertical)
-            .spacing(15)
-            .build();
-            
-        let main_components = Box::builder()
-            .orientation(Orientation::Vertical)
-            .halign(Align::Center)
-            .spacing(50)
-            .margin_start(20)
-            .margin_end(20)
-            .margin_top(20)
-            .margin_bottom(20)
-            .build();
-
-        let scrolled_window = ScrolledWindow::builder()
-            .hscrollbar_policy(PolicyType::Never)
-            .vexpand(true)
-            .child(&main_components)
-            .build();
-
-        main_components.append(&tracking_input_window);
-        main_components.append(&track_button);
-        main_components.append(&package_rows);
-
-        content.append(&header);
-        content.append(&scrolled_window);
-
-        let root_page = NavigationPage::builder()
-            .child(&content)
-            .title("Simple Package Tracker")
-            .build();
-            
-        let window = ApplicationWindow::builder()
-            .application(app)
-            .title("Simple Package Tracker")
-            .default_width(800)
-            .default_height(1100)
-            .build();
-
-        window.set_content(Some(&nav_view));
-        nav_view.push(&root_page);
-        window.present();
+    app.add_main_option(
+        BACKGROUND_OPTION,
+        glib::Char::from(b'b'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Start in the background without opening the window",
+        None,
+    );
+    app.add_main_option(
+        ADD_OPTION,
+        glib::Char::from(b'a'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Track a package",
+        Some("NUMBER"),
+    );
+    app.add_main_option(
+        SHOW_OPTION,
+        glib::Char::from(b's'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Show the details of a package",
+        Some("NUMBER"),
+    );
+
+    // Runs in the primary instance, so launching the app again reaches the open window.
+    let store_clone = store.clone();
+    app.connect_command_line(move |app, command_line| {
+        let options = command_line.options_dict();
+        let window = main_window(app, &store_clone);
+        if let Ok(Some(number)) = options.lookup::<String>(ADD_OPTION) {
+            app.activate_action("track-number", Some(&number.to_variant()));
+        }
+        if let Ok(Some(number)) = options.lookup::<String>(SHOW_OPTION) {
+            app.activate_action("open-details", Some(&normalize(&number).to_variant()));
+        }
+        let files: Vec<gio::File> = command_line
+            .arguments()
+            .iter()
+            .skip(1)
+            .map(|arg| command_line.create_file_for_arg(arg))
+            .collect();
+        if !files.is_empty() {
+            app.open(&files, "");
+        }
+        // Without the background setting nothing would show the hidden window, e.g. when the
+        // autostart entry outlives the setting, so the window opens as usual.
+        let in_background = options.contains(BACKGROUND_OPTION) && storage::load_settings().run_in_background;
+        if !in_background {
+            window.present();
+        }
+        0
+    });
+
+    let store_clone = store.clone();
+    app.connect_open(move |app, files, _| {
+        for file in files {
+            open_uri(app, &file.uri());
+        }
+        main_window(app, &store_clone).present();
     });
 
+    app.connect_activate(move |app| main_window(app, &store).present());
+
     app.run()
 }
+
+/// The window is only hidden while running in the background, so it is shown again as it was.
+fn main_window(app: &Application, store: &PackageStore) -> gtk::Window {
+    app.active_window().unwrap_or_else(|| build_window(app, store).upcast())
+}
+
+/// Registers the application action `name`, forwarding it to the window action of the same name
+/// and building the window first when there is none.
+fn add_window_action(app: &Application, store: &PackageStore, name: &str) {
+    let action = gio::SimpleAction::new(name, Some(glib::VariantTy::STRING));
+    let app_clone = app.clone();
+    let store_clone = store.clone();
+    let window_action = format!("win.{}", name);
+    action.connect_activate(move |_, target| {
+        let window = main_window(&app_clone, &store_clone);
+        if let Err(e) = window.activate_action(&window_action, target) {
+            eprintln!("Failed to activate {}: {}", window_action, e);
+        }
+    });
+    app.add_action(&action);
+}
+
+fn add_mark_seen_action(app: &Application, store: &PackageStore) {
+    let action = gio::SimpleAction::new("mark-seen", Some(glib::VariantTy::STRING));
+    let store = store.clone();
+    action.connect_activate(move |_, target| {
+        if let Some(number) = target.and_then(|target| target.get::<String>()) {
+            store.find_or_load(&number).mark_seen();
+        }
+    });
+    app.add_action(&action);
+}
+
+/// Routes `package-tracker://show/<number>` and `package-tracker://add/<number>` links.
+fn open_uri(app: &Application, uri: &str) {
+    let route = uri
+        .strip_prefix(URI_SCHEME)
+        .and_then(|rest| rest.strip_prefix("://"))
+        .and_then(|rest| rest.split_once('/'));
+    let (action, number) = match route {
+        Some(("show", number)) => ("open-details", normalize(&percent_decode(number))),
+        Some(("add", number)) => ("track-number", normalize(&percent_decode(number))),
+        // Tracking the link itself tells the user it was not recognized.
+        _ => ("track-number", uri.to_string()),
+    };
+    app.activate_action(action, Some(&number.to_variant()));
+}
+
+fn build_window(app: &Application, store: &PackageStore) -> ApplicationWindow {
+    let header = HeaderBar::builder()
+        .show_title_buttons(true)
+        .build();
+
+    let primary_menu = gio::Menu::new();
+    primary_menu.append(Some("Preferences"), Some("win.preferences"));
+    primary_menu.append(Some("Quit"), Some("app.quit"));
+    let menu_button = MenuButton::builder()
+        .icon_name("open-menu-symbolic")
+        .tooltip_markup("Main Menu")
+        .menu_model(&primary_menu)
+        .build();
+    header.pack_end(&menu_button);
+
+    let nav_view = NavigationView::new();
+    let toast_overlay = ToastOverlay::new();
+    let clipboard_banner = Banner::builder()
+        .button_label("Track")
+        .build();
+    let tracking_area = create_tracking_area(
+        store,
+        clipboard_banner.clone(),
+        nav_view.clone(),
+        toast_overlay.clone(),
+    );
+    header.set_title_widget(Some(&tracking_area.quick_add));
+    header.pack_start(&tracking_area.add_buttons);
+
+    let content = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(15)
+        .build();
+        
+    let main_components = Box::builder()
+        .orientation(Orientation::Vertical)
+        .halign(Align::Center)
+        .spacing(50)
+        .margin_start(20)
+        .margin_end(20)
+        .margin_top(20)
+        .margin_bottom(20)
+        .build();
+
+    let scrolled_window = ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Never)
+        .vexpand(true)
+        .child(&main_components)
+        .build();
+
+    main_components.append(&tracking_area.package_area);
+
+    content.append(&header);
+    content.append(&clipboard_banner);
+    content.append(&scrolled_window);
+    content.add_controller(tracking_area.drop_target);
+    content.add_controller(tracking_area.search_shortcut);
+
+    let root_page = NavigationPage::builder()
+        .child(&content)
+        .title("Simple Package Tracker")
+        .build();
+        
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .title("Simple Package Tracker")
+        .default_width(800)
+        .default_height(1100)
+        .build();
+
+    let preferences_action = gio::SimpleAction::new("preferences", None);
+    let window_clone = window.clone();
+    preferences_action.connect_activate(move |_, _| {
+        create_preferences_dialog().present(Some(&window_clone));
+    });
+    window.add_action(&preferences_action);
+
+    let open_details_action = gio::SimpleAction::new("open-details", Some(glib::VariantTy::STRING));
+    let store_clone = store.clone();
+    let nav_view_clone = nav_view.clone();
+    let root_page_clone = root_page.clone();
+    let window_clone = window.clone();
+    open_details_action.connect_activate(move |_, target| {
+        let Some(number) = target.and_then(|target| target.get::<String>()) else {
+            return;
+        };
+        let package = store_clone.find_or_load(&number);
+        nav_view_clone.pop_to_page(&root_page_clone);
+        nav_view_clone.push(&create_details_page(&package, &store_clone));
+        window_clone.present();
+    });
+    window.add_action(&open_details_action);
+    window.add_action(&tracking_area.track_action);
+
+    toast_overlay.set_child(Some(&nav_view));
+    window.set_content(Some(&toast_overlay));
+    nav_view.push(&root_page);
+    window.set_hide_on_close(storage::load_settings().run_in_background);
+    // Deletions waiting on their undo toast are kept once the window is gone.
+    let store_clone = store.clone();
+    window.connect_close_request(move |_| {
+        store_clone.commit_pending_deletions();
+        glib::Propagation::Proceed
+    });
+    window
+}
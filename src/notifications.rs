@@ -0,0 +1,34 @@
+use crate::api::models::StatusMilestone;
+use crate::package::Package;
+use adw::gio::{self, prelude::*};
+
+fn notification_id(number: &str) -> String {
+    format!("package-{}", number)
+}
+
+/// Tells the user that `package` reached `milestone`, with actions to open or dismiss it.
+pub fn notify_milestone(package: &Package, milestone: StatusMilestone) {
+    let Some(app) = gio::Application::default() else {
+        return;
+    };
+    let number = package.number();
+    let target = number.to_variant();
+
+    let notification = gio::Notification::new(milestone.to_string());
+    notification.set_body(Some(&package.title()));
+    notification.set_icon(&gio::ThemedIcon::new(milestone.icon_name()));
+    if matches!(milestone, StatusMilestone::FailedAttempt | StatusMilestone::Exception) {
+        notification.set_priority(gio::NotificationPriority::High);
+    }
+    notification.set_default_action_and_target_value("app.open-details", Some(&target));
+    notification.add_button_with_target_value("Open Details", "app.open-details", Some(&target));
+    notification.add_button_with_target_value("Mark as Seen", "app.mark-seen", Some(&target));
+
+    app.send_notification(Some(&notification_id(&number)), &notification);
+}
+
+pub fn withdraw_notification(number: &str) {
+    if let Some(app) = gio::Application::default() {
+        app.withdraw_notification(&notification_id(number));
+    }
+}
@@ -0,0 +1,111 @@
+mod imp;
+pub mod scheduler;
+pub mod store;
+
+use crate::api::models::{StatusMilestone, TrackingInfo};
+use crate::home_page::sections::Section;
+use crate::home_page::status_badge::status_style;
+use crate::notifications::withdraw_notification;
+use crate::storage::{package_subtitle, package_title, update_package_metadata, PackageMetadata};
+use adw::glib::{self, subclass::prelude::*};
+
+glib::wrapper! {
+    /// A tracked package, shared by every view that displays it.
+    pub struct Package(ObjectSubclass<imp::Package>);
+}
+
+impl Package {
+    pub fn new(number: &str, metadata: &PackageMetadata, added_index: u32) -> Self {
+        let package: Self = glib::Object::builder()
+            .property("number", number)
+            .property("added-index", added_index)
+            .build();
+        package.imp().info.replace(metadata.last_info.clone());
+        package.apply_metadata(metadata);
+        package
+    }
+
+    pub fn info(&self) -> Option<TrackingInfo> {
+        self.imp().info.borrow().clone()
+    }
+
+    /// Replaces the tracking info in place, updating every bound row.
+    pub fn set_info(&self, info: TrackingInfo) {
+        self.imp().info.replace(Some(info));
+        self.update_display();
+    }
+
+    pub fn section(&self) -> Section {
+        self.imp()
+            .info
+            .borrow()
+            .as_ref()
+            .map(Section::from_info)
+            .unwrap_or(Section::WaitingForInfo)
+    }
+
+    pub fn apply_metadata(&self, metadata: &PackageMetadata) {
+        self.set_nickname(metadata.nickname.as_deref());
+        self.set_notes(metadata.notes.as_str());
+        self.set_unseen(metadata.unseen);
+        self.set_muted(metadata.muted);
+        self.update_display();
+    }
+
+    pub fn save_nickname(&self, nickname: Option<String>) {
+        let number = self.number();
+        if update_package_metadata(&number, |meta| meta.nickname = nickname.clone()).is_ok() {
+            self.set_nickname(nickname.as_deref());
+            self.update_display();
+        }
+    }
+
+    pub fn save_notes(&self, notes: &str) {
+        if update_package_metadata(&self.number(), |meta| meta.notes = notes.to_string()).is_ok() {
+            self.set_notes(notes);
+        }
+    }
+
+    /// Clears the new milestone flag along with its notification.
+    pub fn mark_seen(&self) {
+        withdraw_notification(&self.number());
+        if self.unseen() && update_package_metadata(&self.number(), |meta| meta.unseen = false).is_ok() {
+            self.set_unseen(false);
+        }
+    }
+
+    pub fn save_muted(&self, muted: bool) {
+        if update_package_metadata(&self.number(), |meta| meta.muted = muted).is_ok() {
+            self.set_muted(muted);
+        }
+    }
+
+    fn update_display(&self) {
+        let number = self.number();
+        let info = self.imp().info.borrow();
+
+        let status = match info.as_ref() {
+            Some(info) if info.has_error => info
+                .error_message
+                .clone()
+                .unwrap_or_else(|| "No tracking data available".to_string()),
+            Some(info) if !info.events.is_empty() => info.label.clone(),
+            _ => "No tracking data available".to_string(),
+        };
+        let (icon_name, css_class, label) = match info.as_ref() {
+            Some(info) => status_style(info),
+            None => {
+                let milestone = StatusMilestone::Pending;
+                (milestone.icon_name(), milestone.css_class(), milestone.to_string())
+            }
+        };
+        drop(info);
+
+        let nickname = self.nickname();
+        self.set_title(package_title(nickname.as_deref(), &number));
+        self.set_subtitle(package_subtitle(nickname.as_deref(), &number, &status).as_str());
+        self.set_status_icon(icon_name);
+        self.set_status_class(css_class);
+        self.set_status_label(label);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::api::models::TrackingInfo;
+use adw::glib::{self, prelude::*, subclass::prelude::*, Properties};
+use std::cell::{Cell, RefCell};
+
+#[derive(Properties, Default)]
+#[properties(wrapper_type = super::Package)]
+pub struct Package {
+    #[property(get, construct_only)]
+    number: RefCell<String>,
+    #[property(get, set, nullable)]
+    nickname: RefCell<Option<String>>,
+    #[property(get, set)]
+    notes: RefCell<String>,
+    #[property(get, set)]
+    title: RefCell<String>,
+    #[property(get, set)]
+    subtitle: RefCell<String>,
+    #[property(get, set)]
+    status_label: RefCell<String>,
+    #[property(get, set)]
+    status_icon: RefCell<String>,
+    #[property(get, set)]
+    status_class: RefCell<String>,
+    #[property(get, set)]
+    loading: Cell<bool>,
+    #[property(get, set)]
+    selected: Cell<bool>,
+    /// The milestone changed since the details were last opened.
+    #[property(get, set)]
+    unseen: Cell<bool>,
+    #[property(get, set)]
+    muted: Cell<bool>,
+    /// Position of the number in the saved list, used to sort by date added.
+    #[property(get, set)]
+    added_index: Cell<u32>,
+    pub info: RefCell<Option<TrackingInfo>>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for Package {
+    const NAME: &'static str = "SptPackage";
+    type Type = super::Package;
+}
+
+#[glib::derived_properties]
+impl ObjectImpl for Package {}
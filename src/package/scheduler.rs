@@ -0,0 +1,113 @@
+use super::store::PackageStore;
+use super::Package;
+use crate::api::models::StatusMilestone;
+use crate::storage::{load_saved_data, PackageMetadata};
+use adw::glib;
+use chrono::{Duration, Utc};
+
+/// How often the scheduler looks for packages that are due.
+const CHECK_SECONDS: u32 = 60;
+/// Failed refreshes double the interval up to this many times.
+const MAX_BACKOFF_STEPS: u32 = 5;
+const MAX_INTERVAL_DAYS: i64 = 7;
+
+/// Time between refreshes for a package at `milestone`, `None` once it needs no more checks.
+fn base_interval(milestone: Option<StatusMilestone>) -> Option<Duration> {
+    match milestone {
+        Some(StatusMilestone::Delivered) => None,
+        Some(StatusMilestone::OutForDelivery) => Some(Duration::minutes(15)),
+        Some(
+            StatusMilestone::InTransit
+            | StatusMilestone::AvailableForPickup
+            | StatusMilestone::FailedAttempt
+            | StatusMilestone::Exception,
+        ) => Some(Duration::hours(1)),
+        Some(StatusMilestone::InfoReceived | StatusMilestone::Pending) | None => Some(Duration::days(1)),
+    }
+}
+
+/// The base interval, doubled for each refresh in a row that failed.
+fn refresh_interval(milestone: Option<StatusMilestone>, metadata: &PackageMetadata) -> Option<Duration> {
+    let backoff = 2_i32.pow(metadata.failed_checks.min(MAX_BACKOFF_STEPS));
+    base_interval(milestone).map(|interval| (interval * backoff).min(Duration::days(MAX_INTERVAL_DAYS)))
+}
+
+/// Packages whose interval elapsed, most urgent first and limited to what is left of the
+/// monthly request budget.
+fn due_packages(store: &PackageStore) -> Vec<Package> {
+    let data = load_saved_data();
+    let now = Utc::now();
+    let mut due: Vec<(Duration, Package)> = store
+        .packages()
+        .into_iter()
+        .filter_map(|package| {
+            let metadata = data.packages.get(&package.number()).cloned().unwrap_or_default();
+            let milestone = package.info().filter(|info| !info.has_error).map(|info| info.milestone());
+            let interval = refresh_interval(milestone, &metadata)?;
+            let is_due = metadata
+                .last_checked
+                .is_none_or(|last_checked| now - last_checked >= interval);
+            is_due.then_some((interval, package))
+        })
+        .collect();
+    due.sort_by_key(|(interval, _)| *interval);
+
+    let mut packages: Vec<Package> = due.into_iter().map(|(_, package)| package).collect();
+    let quota = data.settings.monthly_quota;
+    if quota > 0 {
+        packages.truncate(quota.saturating_sub(data.quota.used()) as usize);
+    }
+    packages
+}
+
+/// Refreshes the packages of `store` in the background as their intervals elapse, archiving
+/// the ones delivered long enough ago.
+pub fn start_auto_refresh(store: &PackageStore) {
+    let store = store.clone();
+    glib::spawn_future_local(async move {
+        loop {
+            store.archive_delivered();
+            if load_saved_data().settings.auto_refresh {
+                let due = due_packages(&store);
+                store.refresh(&due).await;
+            }
+            glib::timeout_future_seconds(CHECK_SECONDS).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(milestone: Option<StatusMilestone>, failed_checks: u32) -> Option<Duration> {
+        let metadata = PackageMetadata { failed_checks, ..Default::default() };
+        refresh_interval(milestone, &metadata)
+    }
+
+    #[test]
+    fn intervals_follow_the_milestone() {
+        assert_eq!(interval(Some(StatusMilestone::Delivered), 0), None);
+        assert_eq!(interval(Some(StatusMilestone::OutForDelivery), 0), Some(Duration::minutes(15)));
+        assert_eq!(interval(Some(StatusMilestone::InTransit), 0), Some(Duration::hours(1)));
+        assert_eq!(interval(Some(StatusMilestone::Exception), 0), Some(Duration::hours(1)));
+        assert_eq!(interval(Some(StatusMilestone::InfoReceived), 0), Some(Duration::days(1)));
+        assert_eq!(interval(None, 0), Some(Duration::days(1)));
+    }
+
+    #[test]
+    fn failed_checks_double_the_interval() {
+        assert_eq!(interval(Some(StatusMilestone::OutForDelivery), 1), Some(Duration::minutes(30)));
+        assert_eq!(interval(Some(StatusMilestone::InTransit), 4), Some(Duration::hours(16)));
+        assert_eq!(interval(Some(StatusMilestone::InTransit), 5), Some(Duration::hours(32)));
+    }
+
+    #[test]
+    fn backoff_stops_growing() {
+        assert_eq!(interval(Some(StatusMilestone::InTransit), 6), Some(Duration::hours(32)));
+        assert_eq!(interval(Some(StatusMilestone::InTransit), 100), Some(Duration::hours(32)));
+        assert_eq!(interval(None, 2), Some(Duration::days(4)));
+        assert_eq!(interval(None, 3), Some(Duration::days(MAX_INTERVAL_DAYS)));
+        assert_eq!(interval(Some(StatusMilestone::Delivered), 3), None);
+    }
+}
@@ -0,0 +1,314 @@
+use super::Package;
+use crate::api::models::TrackingInfo;
+use crate::api::tracking::TrackingClient;
+use crate::notifications::notify_milestone;
+use crate::storage::{
+    load_saved_data, load_tracking_numbers, save_saved_data, save_tracking_numbers, PackageMetadata,
+};
+use crate::tracking_number::normalize::normalize;
+use adw::{gio, prelude::*, Toast, ToastOverlay};
+use chrono::{Duration, Utc};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type UpdatedHandlers = Rc<RefCell<Vec<Rc<dyn Fn()>>>>;
+
+/// The tracked, non-archived packages, backed by a `gio::ListStore` that views bind to.
+#[derive(Clone)]
+pub struct PackageStore {
+    model: gio::ListStore,
+    updated_handlers: UpdatedHandlers,
+    /// Numbers deleted while their undo toast is shown, still saved until the deletion is committed.
+    pending_deletions: Rc<RefCell<Vec<String>>>,
+}
+
+impl PackageStore {
+    pub fn new() -> Self {
+        let store = Self {
+            model: gio::ListStore::new::<Package>(),
+            updated_handlers: Rc::new(RefCell::new(Vec::new())),
+            pending_deletions: Rc::new(RefCell::new(Vec::new())),
+        };
+        store.archive_delivered();
+        store.sync_with_storage();
+        store
+    }
+
+    pub fn model(&self) -> &gio::ListStore {
+        &self.model
+    }
+
+    pub fn packages(&self) -> Vec<Package> {
+        self.model.iter::<Package>().filter_map(Result::ok).collect()
+    }
+
+    pub fn find(&self, number: &str) -> Option<Package> {
+        self.packages().into_iter().find(|package| package.number() == number)
+    }
+
+    /// The package from the list, or built from storage when it is archived.
+    pub fn find_or_load(&self, number: &str) -> Package {
+        self.find(number).unwrap_or_else(|| {
+            let metadata = load_saved_data().packages.get(number).cloned().unwrap_or_default();
+            Package::new(number, &metadata, 0)
+        })
+    }
+
+    /// Runs `handler` whenever package infos changed in place, so views can re-filter and re-sort.
+    pub fn connect_updated(&self, handler: impl Fn() + 'static) {
+        self.updated_handlers.borrow_mut().push(Rc::new(handler));
+    }
+
+    fn emit_updated(&self) {
+        let handlers = self.updated_handlers.borrow().clone();
+        for handler in handlers {
+            handler();
+        }
+    }
+
+    pub fn append(&self, package: &Package) {
+        self.model.append(package);
+    }
+
+    pub fn remove(&self, package: &Package) -> bool {
+        match self.model.find(package) {
+            Some(position) => {
+                self.model.remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds the packages saved in storage that are missing and drops the removed, archived or
+    /// deleted ones.
+    pub fn sync_with_storage(&self) {
+        let data = load_saved_data();
+        let pending_deletions = self.pending_deletions.borrow().clone();
+        let active: Vec<(u32, &String)> = data
+            .tracking_numbers
+            .iter()
+            .enumerate()
+            .filter(|(_, number)| !data.packages.get(*number).is_some_and(|meta| meta.archived))
+            .filter(|(_, number)| !pending_deletions.contains(number))
+            .map(|(index, number)| (index as u32, number))
+            .collect();
+
+        for package in self.packages() {
+            if !active.iter().any(|(_, number)| **number == package.number()) {
+                self.remove(&package);
+            }
+        }
+
+        for (index, number) in active {
+            match self.find(number) {
+                Some(package) => package.set_added_index(index),
+                None => {
+                    let metadata = data.packages.get(number).cloned().unwrap_or_default();
+                    self.model.append(&Package::new(number, &metadata, index));
+                }
+            }
+        }
+        self.emit_updated();
+    }
+
+    /// Archives the packages whose saved info shows they were delivered long enough ago, without
+    /// asking the API since delivered packages are no longer refreshed.
+    pub fn archive_delivered(&self) {
+        let mut data = load_saved_data();
+        let auto_archive_days = data.settings.auto_archive_days;
+        let mut archived_any = false;
+        for number in &data.tracking_numbers {
+            if let Some(meta) = data.packages.get_mut(number) {
+                archived_any |= auto_archive(meta, auto_archive_days);
+            }
+        }
+        if archived_any {
+            let _ = save_saved_data(&data);
+            self.sync_with_storage();
+        }
+    }
+
+    /// Saves the entered numbers, moving the archived ones back to the tracked packages.
+    pub fn add_numbers(&self, input: &str) {
+        let mut data = load_saved_data();
+        data.tracking_numbers = clean_numbers_list(input);
+        for number in input.lines().map(normalize) {
+            self.pending_deletions.borrow_mut().retain(|pending| *pending != number);
+            if let Some(meta) = data.packages.get_mut(&number) {
+                meta.restore();
+            }
+        }
+        let _ = save_saved_data(&data);
+        self.sync_with_storage();
+    }
+
+    /// Fetches fresh tracking info and updates the packages in place, skipping the ones another
+    /// refresh is already fetching.
+    pub async fn refresh(&self, packages: &[Package]) {
+        let packages: Vec<Package> = packages.iter().filter(|package| !package.loading()).cloned().collect();
+        if packages.is_empty() {
+            return;
+        }
+        for package in &packages {
+            package.set_loading(true);
+        }
+
+        let mut data = load_saved_data();
+        let numbers: Vec<(String, Option<String>)> = packages
+            .iter()
+            .map(|package| {
+                let number = package.number();
+                let courier_code = data
+                    .packages
+                    .get(&number)
+                    .and_then(|meta| meta.courier_code.clone());
+                (number, courier_code)
+            })
+            .collect();
+        let results = TrackingClient::new().track_numbers(&numbers).await;
+
+        data = load_saved_data();
+        data.quota.record(numbers.len() as u32);
+        let auto_archive_days = data.settings.auto_archive_days;
+        let mut milestone_changes = Vec::new();
+        for (number, info) in results {
+            let Some(package) = packages.iter().find(|package| package.number() == number) else {
+                continue;
+            };
+
+            let mut archived = false;
+            if data.tracking_numbers.contains(&number) {
+                let meta = data.packages.entry(number).or_default();
+                if let Some(milestone) = meta.record_check(&info) {
+                    package.set_unseen(true);
+                    if !meta.muted {
+                        milestone_changes.push((package.clone(), milestone));
+                    }
+                }
+                auto_archive(meta, auto_archive_days);
+                archived = meta.archived;
+            }
+
+            package.set_info(info);
+            if archived {
+                self.remove(package);
+            }
+        }
+        let _ = save_saved_data(&data);
+
+        if data.settings.notifications {
+            for (package, milestone) in milestone_changes {
+                notify_milestone(&package, milestone);
+            }
+        }
+        for package in &packages {
+            package.set_loading(false);
+        }
+        self.emit_updated();
+    }
+
+    /// Deletes the packages once their undo toast is dismissed, taking them off the list
+    /// meanwhile. `on_undo` runs when the user takes the deletion back.
+    pub fn delete_with_undo(&self, numbers: Vec<String>, toast_overlay: &ToastOverlay, on_undo: impl Fn() + 'static) {
+        if numbers.is_empty() {
+            return;
+        }
+        self.pending_deletions.borrow_mut().extend(numbers.iter().cloned());
+        for number in &numbers {
+            if let Some(package) = self.find(number) {
+                self.remove(&package);
+            }
+        }
+
+        let title = match numbers.len() {
+            1 => "Package removed".to_string(),
+            count => format!("{} packages removed", count),
+        };
+        let toast = Toast::builder()
+            .title(&title)
+            .button_label("Undo")
+            .timeout(5)
+            .build();
+
+        let store = self.clone();
+        let numbers_clone = numbers.clone();
+        toast.connect_button_clicked(move |_| {
+            store
+                .pending_deletions
+                .borrow_mut()
+                .retain(|pending| !numbers_clone.contains(pending));
+            store.sync_with_storage();
+            on_undo();
+        });
+
+        let store = self.clone();
+        toast.connect_dismissed(move |_| store.commit_deletions(&numbers));
+        toast_overlay.add_toast(toast);
+    }
+
+    /// Whether the package was deleted and only waits for its undo toast to go away.
+    pub fn is_pending_deletion(&self, number: &str) -> bool {
+        self.pending_deletions.borrow().iter().any(|pending| pending == number)
+    }
+
+    /// Removes from storage the numbers whose deletion is still pending.
+    fn commit_deletions(&self, numbers: &[String]) {
+        let committed: Vec<String> = {
+            let mut pending = self.pending_deletions.borrow_mut();
+            let committed = pending.iter().filter(|number| numbers.contains(number)).cloned().collect();
+            pending.retain(|number| !numbers.contains(number));
+            committed
+        };
+        if committed.is_empty() {
+            return;
+        }
+        let remaining_numbers: Vec<String> = load_tracking_numbers()
+            .into_iter()
+            .filter(|number| !committed.contains(number))
+            .collect();
+        let _ = save_tracking_numbers(&remaining_numbers);
+    }
+
+    /// Commits every pending deletion, for when the window closes or the app quits.
+    pub fn commit_pending_deletions(&self) {
+        let numbers = self.pending_deletions.borrow().clone();
+        self.commit_deletions(&numbers);
+    }
+
+    pub async fn refresh_all(&self) {
+        self.sync_with_storage();
+        self.refresh(&self.packages()).await;
+    }
+}
+
+/// Archives the package when its last known info says it was delivered long enough ago, unless
+/// the user moved it back from the archive. Returns whether it was archived now.
+fn auto_archive(meta: &mut PackageMetadata, auto_archive_days: u32) -> bool {
+    let due = meta
+        .last_info
+        .as_ref()
+        .is_some_and(|info| !info.has_error && should_auto_archive(info, auto_archive_days));
+    if meta.archived || meta.auto_archive_dismissed || !due {
+        return false;
+    }
+    meta.archived = true;
+    true
+}
+
+fn should_auto_archive(info: &TrackingInfo, auto_archive_days: u32) -> bool {
+    auto_archive_days > 0
+        && info
+            .delivered_at()
+            .is_some_and(|delivered_at| Utc::now() - delivered_at >= Duration::days(auto_archive_days.into()))
+}
+
+pub fn clean_numbers_list(input: &str) -> Vec<String> {
+    let mut all_numbers = load_tracking_numbers();
+    for number in input.lines().map(normalize).filter(|l| !l.is_empty()) {
+        if !all_numbers.contains(&number) {
+            all_numbers.push(number);
+        }
+    }
+    all_numbers
+}
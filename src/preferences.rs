@@ -0,0 +1,256 @@
+use crate::background::apply_background_settings;
+use crate::email::keyring::{load_password, save_password};
+use crate::storage::{load_saved_data, load_settings, save_settings, MailboxSettings};
+use adw::{
+    glib, prelude::*, EntryRow, PasswordEntryRow, PreferencesDialog, PreferencesGroup,
+    PreferencesPage, SpinRow, SwitchRow, Toast,
+};
+
+fn save_mailbox_settings(update: impl FnOnce(&mut MailboxSettings)) {
+    let mut settings = load_settings();
+    update(&mut settings.mailbox);
+    let _ = save_settings(&settings);
+}
+
+fn create_mailbox_page() -> PreferencesPage {
+    let mailbox = load_settings().mailbox;
+
+    let enabled_row = SwitchRow::builder()
+        .title("Watch Mailbox")
+        .subtitle("Add tracking numbers from new shipping emails")
+        .active(mailbox.enabled)
+        .build();
+    enabled_row.connect_active_notify(|row| {
+        let enabled = row.is_active();
+        save_mailbox_settings(|mailbox| mailbox.enabled = enabled);
+    });
+
+    let folder_row = EntryRow::builder()
+        .title("Folder")
+        .text(&mailbox.folder)
+        .show_apply_button(true)
+        .build();
+    folder_row.connect_apply(|row| {
+        let folder = row.text().trim().to_string();
+        save_mailbox_settings(|mailbox| mailbox.folder = folder);
+    });
+
+    let poll_row = SpinRow::builder()
+        .title("Check Every")
+        .subtitle("Minutes between checks")
+        .build();
+    poll_row.set_range(1.0, 1440.0);
+    poll_row.adjustment().set_step_increment(1.0);
+    poll_row.set_value(mailbox.poll_minutes as f64);
+    poll_row.connect_value_notify(|row| {
+        let minutes = row.value() as u32;
+        save_mailbox_settings(|mailbox| mailbox.poll_minutes = minutes);
+    });
+
+    let watcher_group = PreferencesGroup::builder()
+        .title("Mailbox Watcher")
+        .description("Processed emails are flagged on the server so they are only read once")
+        .build();
+    watcher_group.add(&enabled_row);
+    watcher_group.add(&folder_row);
+    watcher_group.add(&poll_row);
+
+    let host_row = EntryRow::builder()
+        .title("IMAP Server")
+        .text(&mailbox.host)
+        .show_apply_button(true)
+        .build();
+    host_row.connect_apply(|row| {
+        let host = row.text().trim().to_string();
+        save_mailbox_settings(|mailbox| mailbox.host = host);
+    });
+
+    let port_row = SpinRow::builder().title("Port").build();
+    port_row.set_range(1.0, 65535.0);
+    port_row.adjustment().set_step_increment(1.0);
+    port_row.set_value(mailbox.port as f64);
+    port_row.connect_value_notify(|row| {
+        let port = row.value() as u16;
+        save_mailbox_settings(|mailbox| mailbox.port = port);
+    });
+
+    let tls_row = SwitchRow::builder()
+        .title("Use TLS")
+        .subtitle("Only turn off for a local test server")
+        .active(mailbox.use_tls)
+        .build();
+    tls_row.connect_active_notify(|row| {
+        let use_tls = row.is_active();
+        save_mailbox_settings(|mailbox| mailbox.use_tls = use_tls);
+    });
+
+    let username_row = EntryRow::builder()
+        .title("Username")
+        .text(&mailbox.username)
+        .show_apply_button(true)
+        .build();
+    username_row.connect_apply(|row| {
+        let username = row.text().trim().to_string();
+        save_mailbox_settings(|mailbox| mailbox.username = username);
+    });
+
+    // The password lives in the keyring, it is filled in once read from there.
+    let password_row = PasswordEntryRow::builder()
+        .title("Password")
+        .show_apply_button(true)
+        .build();
+    let password_row_clone = password_row.clone();
+    glib::spawn_future_local(async move {
+        match load_password(&mailbox.password_id).await {
+            Ok(password) => password_row_clone.set_text(&password.unwrap_or_default()),
+            Err(e) => eprintln!("Failed to read the mailbox password: {}", e),
+        }
+    });
+    password_row.connect_apply(|row| {
+        let row = row.clone();
+        glib::spawn_future_local(async move {
+            if let Err(e) = save_password(&row.text()).await {
+                let dialog = row.ancestor(PreferencesDialog::static_type()).and_downcast::<PreferencesDialog>();
+                if let Some(dialog) = dialog {
+                    dialog.add_toast(Toast::new(&format!("Failed to save the password: {}", e)));
+                }
+            }
+        });
+    });
+
+    let server_group = PreferencesGroup::builder().title("Server").build();
+    server_group.add(&host_row);
+    server_group.add(&port_row);
+    server_group.add(&tls_row);
+    server_group.add(&username_row);
+    server_group.add(&password_row);
+
+    let mailbox_page = PreferencesPage::builder()
+        .title("Mailbox")
+        .icon_name("mail-unread-symbolic")
+        .build();
+    mailbox_page.add(&watcher_group);
+    mailbox_page.add(&server_group);
+    mailbox_page
+}
+
+pub fn create_preferences_dialog() -> PreferencesDialog {
+    let settings = load_settings();
+
+    let auto_archive_row = SpinRow::builder()
+        .title("Archive Delivered Packages")
+        .subtitle("Days after delivery, 0 to never archive automatically")
+        .build();
+    auto_archive_row.set_range(0.0, 365.0);
+    auto_archive_row.adjustment().set_step_increment(1.0);
+    auto_archive_row.set_value(settings.auto_archive_days as f64);
+
+    auto_archive_row.connect_value_notify(|row| {
+        let mut settings = load_settings();
+        settings.auto_archive_days = row.value() as u32;
+        let _ = save_settings(&settings);
+    });
+
+    let archive_group = PreferencesGroup::builder().title("Archive").build();
+    archive_group.add(&auto_archive_row);
+
+    let auto_refresh_row = SwitchRow::builder()
+        .title("Refresh Automatically")
+        .subtitle("Check packages more often as they get closer to delivery")
+        .active(settings.auto_refresh)
+        .build();
+    auto_refresh_row.connect_active_notify(|row| {
+        let mut settings = load_settings();
+        settings.auto_refresh = row.is_active();
+        let _ = save_settings(&settings);
+    });
+
+    let quota_row = SpinRow::builder()
+        .title("Monthly Request Budget")
+        .subtitle(format!(
+            "{} requests used this month, 0 for no limit",
+            load_saved_data().quota.used()
+        ))
+        .build();
+    quota_row.set_range(0.0, 100_000.0);
+    quota_row.adjustment().set_step_increment(10.0);
+    quota_row.set_value(settings.monthly_quota as f64);
+    quota_row.connect_value_notify(|row| {
+        let mut settings = load_settings();
+        settings.monthly_quota = row.value() as u32;
+        let _ = save_settings(&settings);
+    });
+
+    let refresh_group = PreferencesGroup::builder()
+        .title("Automatic Refresh")
+        .description("Delivered and archived packages are not refreshed, failing ones are checked less often")
+        .build();
+    refresh_group.add(&auto_refresh_row);
+    refresh_group.add(&quota_row);
+
+    let background_row = SwitchRow::builder()
+        .title("Run in Background")
+        .subtitle("Keep refreshing packages and sending notifications after the window is closed")
+        .active(settings.run_in_background)
+        .build();
+    background_row.connect_active_notify(|row| {
+        let mut settings = load_settings();
+        settings.run_in_background = row.is_active();
+        let _ = save_settings(&settings);
+        apply_background_settings();
+    });
+
+    let autostart_row = SwitchRow::builder()
+        .title("Start at Login")
+        .subtitle("Start in the background when you log in")
+        .active(settings.autostart)
+        .build();
+    background_row
+        .bind_property("active", &autostart_row, "sensitive")
+        .sync_create()
+        .build();
+    autostart_row.connect_active_notify(|row| {
+        let mut settings = load_settings();
+        settings.autostart = row.is_active();
+        let _ = save_settings(&settings);
+        apply_background_settings();
+    });
+
+    let background_group = PreferencesGroup::builder()
+        .title("Background")
+        .description("Quit from the main menu while running in the background")
+        .build();
+    background_group.add(&background_row);
+    background_group.add(&autostart_row);
+
+    let notifications_row = SwitchRow::builder()
+        .title("Status Changes")
+        .subtitle("Notify when a package is out for delivery, delivered or has a problem")
+        .active(settings.notifications)
+        .build();
+    notifications_row.connect_active_notify(|row| {
+        let mut settings = load_settings();
+        settings.notifications = row.is_active();
+        let _ = save_settings(&settings);
+    });
+
+    let notifications_group = PreferencesGroup::builder()
+        .title("Notifications")
+        .description("Packages can also be muted from their menu")
+        .build();
+    notifications_group.add(&notifications_row);
+
+    let general_page = PreferencesPage::builder()
+        .title("General")
+        .icon_name("preferences-system-symbolic")
+        .build();
+    general_page.add(&archive_group);
+    general_page.add(&refresh_group);
+    general_page.add(&notifications_group);
+    general_page.add(&background_group);
+
+    let dialog = PreferencesDialog::new();
+    dialog.add(&general_page);
+    dialog.add(&create_mailbox_page());
+    dialog
+}
@@ -1,11 +1,306 @@
+use crate::api::models::{StatusMilestone, TrackingInfo};
+use crate::tracking_number::normalize::normalize;
+use chrono::{DateTime, Local, Utc};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+const DEFAULT_AUTO_ARCHIVE_DAYS: u32 = 7;
+/// Bumped whenever saved data needs a migration, see `migrate_saved_data`.
+const DATA_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Default)]
 pub struct SavedData {
     pub tracking_numbers: Vec<String>,
+    #[serde(default)]
+    pub packages: HashMap<String, PackageMetadata>,
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub quota: QuotaUsage,
+}
+
+/// Ship24 requests made during the current month, checked against the monthly budget.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct QuotaUsage {
+    pub month: String,
+    pub requests: u32,
+}
+
+impl QuotaUsage {
+    fn current_month() -> String {
+        Local::now().format("%Y-%m").to_string()
+    }
+
+    pub fn used(&self) -> u32 {
+        if self.month == Self::current_month() {
+            self.requests
+        } else {
+            0
+        }
+    }
+
+    pub fn record(&mut self, requests: u32) {
+        let month = Self::current_month();
+        if self.month != month {
+            self.month = month;
+            self.requests = 0;
+        }
+        self.requests += requests;
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct PackageMetadata {
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub last_info: Option<TrackingInfo>,
+    #[serde(default)]
+    pub nickname: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    /// Ship24 courier code taken from the tracking link the package was added from.
+    #[serde(default)]
+    pub courier_code: Option<String>,
+    /// The milestone changed since the user last looked at the package.
+    #[serde(default)]
+    pub unseen: bool,
+    /// No notifications for this package.
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default)]
+    pub last_checked: Option<DateTime<Utc>>,
+    /// Refreshes in a row that failed, slowing down automatic refreshes.
+    #[serde(default)]
+    pub failed_checks: u32,
+    /// Restored from the archive by the user, so it is not archived automatically again.
+    #[serde(default)]
+    pub auto_archive_dismissed: bool,
+}
+
+/// The milestone `info` reached if it differs from the one `previous` had. A package seen for
+/// the first time or failing to refresh has no previous milestone to compare with.
+fn changed_milestone(previous: Option<&TrackingInfo>, info: &TrackingInfo) -> Option<StatusMilestone> {
+    let previous = previous.filter(|previous| !previous.has_error)?.milestone();
+    let milestone = info.milestone();
+    (!info.has_error && milestone != previous).then_some(milestone)
+}
+
+impl PackageMetadata {
+    pub fn title<'a>(&'a self, number: &'a str) -> &'a str {
+        package_title(self.nickname.as_deref(), number)
+    }
+
+    /// Moves an archived package back to the tracked ones for good.
+    pub fn restore(&mut self) {
+        if self.archived {
+            self.archived = false;
+            self.auto_archive_dismissed = true;
+        }
+    }
+
+    /// Stores the outcome of a refresh, returning the milestone reached when it changed.
+    pub fn record_check(&mut self, info: &TrackingInfo) -> Option<StatusMilestone> {
+        self.last_checked = Some(Utc::now());
+        if info.has_error {
+            self.failed_checks += 1;
+            return None;
+        }
+        self.failed_checks = 0;
+        let milestone = changed_milestone(self.last_info.as_ref(), info);
+        self.unseen |= milestone.is_some();
+        self.last_info = Some(info.clone());
+        milestone
+    }
+
+    /// Folds the metadata of a duplicate entry into this one, keeping what each side knows.
+    fn merge(&mut self, other: PackageMetadata) {
+        self.archived &= other.archived;
+        self.unseen |= other.unseen;
+        self.muted |= other.muted;
+        self.auto_archive_dismissed |= other.auto_archive_dismissed;
+        self.last_checked = self.last_checked.max(other.last_checked);
+        if self.nickname.is_none() {
+            self.nickname = other.nickname;
+        }
+        if self.courier_code.is_none() {
+            self.courier_code = other.courier_code;
+        }
+        if self.notes.is_empty() {
+            self.notes = other.notes;
+        } else if !other.notes.is_empty() && other.notes != self.notes {
+            self.notes = format!("{}\n{}", self.notes, other.notes);
+        }
+        let latest_event = |info: &Option<TrackingInfo>| {
+            info.as_ref()
+                .and_then(|info| info.latest_event())
+                .map(|event| event.occurrence_datetime)
+        };
+        if latest_event(&other.last_info) > latest_event(&self.last_info) {
+            self.last_info = other.last_info;
+        }
+    }
+
+    pub fn subtitle(&self, number: &str, status: &str) -> String {
+        package_subtitle(self.nickname.as_deref(), number, status)
+    }
+}
+
+/// The nickname if one is set, otherwise the tracking number itself.
+pub fn package_title<'a>(nickname: Option<&'a str>, number: &'a str) -> &'a str {
+    nickname.unwrap_or(number)
+}
+
+/// Row subtitle that keeps the tracking number visible once a nickname replaces it as title.
+pub fn package_subtitle(nickname: Option<&str>, number: &str, status: &str) -> String {
+    match nickname {
+        Some(_) => format!("{}\n{}", number, status),
+        None => status.to_string(),
+    }
+}
+
+/// Order of the packages on the home page, see `home_page::sorting` for the comparisons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortKey {
+    #[default]
+    DateAdded,
+    LastEvent,
+    EstimatedDelivery,
+    Severity,
+    Nickname,
+    Courier,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 6] = [
+        Self::DateAdded,
+        Self::LastEvent,
+        Self::EstimatedDelivery,
+        Self::Severity,
+        Self::Nickname,
+        Self::Courier,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::DateAdded => "date-added",
+            Self::LastEvent => "last-event",
+            Self::EstimatedDelivery => "estimated-delivery",
+            Self::Severity => "severity",
+            Self::Nickname => "nickname",
+            Self::Courier => "courier",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|sort_key| sort_key.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::DateAdded => "Date Added",
+            Self::LastEvent => "Last Event",
+            Self::EstimatedDelivery => "Estimated Delivery",
+            Self::Severity => "Status Severity",
+            Self::Nickname => "Nickname",
+            Self::Courier => "Courier",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Settings {
+    /// Days after delivery before a package is archived, `0` disables auto-archiving.
+    #[serde(default = "default_auto_archive_days")]
+    pub auto_archive_days: u32,
+    #[serde(default)]
+    pub collapsed_sections: Vec<String>,
+    #[serde(default)]
+    pub sort_key: SortKey,
+    #[serde(default)]
+    pub sort_descending: bool,
+    #[serde(default)]
+    pub mailbox: MailboxSettings,
+    /// Desktop notifications when a package reaches a new milestone.
+    #[serde(default = "default_notifications")]
+    pub notifications: bool,
+    #[serde(default = "default_auto_refresh")]
+    pub auto_refresh: bool,
+    /// Ship24 requests allowed per month for automatic refreshes, `0` for no limit.
+    #[serde(default)]
+    pub monthly_quota: u32,
+    /// Keep the app running after its window is closed.
+    #[serde(default)]
+    pub run_in_background: bool,
+    /// Start in the background at login, only used when running in the background.
+    #[serde(default)]
+    pub autostart: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            auto_archive_days: DEFAULT_AUTO_ARCHIVE_DAYS,
+            collapsed_sections: Vec::new(),
+            sort_key: SortKey::default(),
+            sort_descending: false,
+            mailbox: MailboxSettings::default(),
+            notifications: true,
+            auto_refresh: true,
+            monthly_quota: 0,
+            run_in_background: false,
+            autostart: false,
+        }
+    }
+}
+
+/// IMAP folder watched for shipping emails.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MailboxSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// Plain TCP is only meant for local test servers.
+    pub use_tls: bool,
+    pub username: String,
+    /// Names the keyring item holding the password, empty while none is saved.
+    pub password_id: String,
+    pub folder: String,
+    pub poll_minutes: u32,
+}
+
+impl Default for MailboxSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 993,
+            use_tls: true,
+            username: String::new(),
+            password_id: String::new(),
+            folder: "INBOX".to_string(),
+            poll_minutes: 15,
+        }
+    }
+}
+
+fn default_auto_archive_days() -> u32 {
+    DEFAULT_AUTO_ARCHIVE_DAYS
+}
+
+fn default_notifications() -> bool {
+    true
+}
+
+fn default_auto_refresh() -> bool {
+    true
 }
 
 pub fn get_data_file() -> Option<PathBuf> {
@@ -22,21 +317,96 @@ pub fn get_data_file() -> Option<PathBuf> {
     })
 }
 
-pub fn save_tracking_numbers(numbers: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+pub fn load_saved_data() -> SavedData {
+    get_data_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<SavedData>(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_saved_data(data: &SavedData) -> Result<(), Box<dyn std::error::Error>> {
     if let Some(file_path) = get_data_file() {
-        let data = SavedData {
-            tracking_numbers: numbers.to_vec(),
-        };
-        let json = serde_json::to_string(&data)?;
+        let json = serde_json::to_string(data)?;
         fs::write(file_path, json)?;
     }
     Ok(())
 }
 
+pub fn save_tracking_numbers(numbers: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut data = load_saved_data();
+    data.tracking_numbers = numbers.to_vec();
+    data.packages.retain(|number, _| numbers.contains(number));
+    save_saved_data(&data)
+}
+
 pub fn load_tracking_numbers() -> Vec<String> {
-    get_data_file()
-        .and_then(|path| fs::read_to_string(path).ok())
-        .and_then(|content| serde_json::from_str::<SavedData>(&content).ok())
-        .map(|data| data.tracking_numbers)
-        .unwrap_or_default()
+    load_saved_data().tracking_numbers
+}
+
+pub fn update_package_metadata(
+    number: &str,
+    update: impl FnOnce(&mut PackageMetadata),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut data = load_saved_data();
+    update(data.packages.entry(number.to_string()).or_default());
+    save_saved_data(&data)
+}
+
+/// Upgrades data saved by older versions: tracking numbers are normalized once and the
+/// duplicates this reveals are merged into a single package.
+pub fn migrate_saved_data() {
+    let mut data = load_saved_data();
+    if data.version >= DATA_VERSION {
+        return;
+    }
+
+    let mut numbers: Vec<String> = Vec::with_capacity(data.tracking_numbers.len());
+    let mut packages: HashMap<String, PackageMetadata> = HashMap::new();
+    for number in std::mem::take(&mut data.tracking_numbers) {
+        let normalized = normalize(&number);
+        if !numbers.contains(&normalized) {
+            numbers.push(normalized.clone());
+        }
+        if let Some(metadata) = data.packages.remove(&number) {
+            match packages.get_mut(&normalized) {
+                Some(existing) => existing.merge(metadata),
+                None => {
+                    packages.insert(normalized, metadata);
+                }
+            }
+        }
+    }
+
+    data.tracking_numbers = numbers;
+    data.packages = packages;
+    data.version = DATA_VERSION;
+    let _ = save_saved_data(&data);
+}
+
+pub fn load_settings() -> Settings {
+    load_saved_data().settings
+}
+
+pub fn save_settings(settings: &Settings) -> Result<(), Box<dyn std::error::Error>> {
+    let mut data = load_saved_data();
+    data.settings = settings.clone();
+    save_saved_data(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quota_rolls_over_with_the_month() {
+        let mut quota = QuotaUsage { month: "2000-01".to_string(), requests: 40 };
+        assert_eq!(quota.used(), 0);
+
+        quota.record(3);
+        assert_eq!(quota.month, QuotaUsage::current_month());
+        assert_eq!(quota.used(), 3);
+
+        quota.record(2);
+        assert_eq!(quota.used(), 5);
+    }
 }
@@ -0,0 +1,127 @@
+pub mod extract;
+pub mod normalize;
+pub mod url;
+pub mod validate;
+
+use validate::{validate, Validation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Carrier {
+    Ups,
+    Usps,
+    FedEx,
+    Dhl,
+    RoyalMail,
+    Amazon,
+    /// International postal items following the UPU S10 standard.
+    Upu,
+}
+
+impl Carrier {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ups => "UPS",
+            Self::Usps => "USPS",
+            Self::FedEx => "FedEx",
+            Self::Dhl => "DHL",
+            Self::RoyalMail => "Royal Mail",
+            Self::Amazon => "Amazon Logistics",
+            Self::Upu => "Postal service",
+        }
+    }
+
+    /// Courier code sent to Ship24 as a hint for `number`, when the carrier maps to a single one.
+    /// Ship24's "dhl" is DHL Express, so DHL Parcel (JJD) and eCommerce (GM) numbers get none.
+    pub fn ship24_code(&self, number: &str) -> Option<&'static str> {
+        match self {
+            Self::Ups => Some("ups"),
+            Self::Usps => Some("us-post"),
+            Self::FedEx => Some("fedex"),
+            Self::Dhl if all_digits(number) => Some("dhl"),
+            Self::Dhl | Self::RoyalMail | Self::Amazon | Self::Upu => None,
+        }
+    }
+}
+
+fn all_digits(number: &str) -> bool {
+    number.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_s10(number: &str) -> bool {
+    let bytes = number.as_bytes();
+    bytes.len() == 13
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && all_digits(&number[2..11])
+        && bytes[11..].iter().all(u8::is_ascii_uppercase)
+}
+
+/// Carriers whose number format matches, most specific first. Digit-only
+/// numbers can match several carriers, the check digit decides between them.
+fn format_matches(number: &str) -> Vec<Carrier> {
+    if !number.is_ascii() {
+        return Vec::new();
+    }
+    let len = number.len();
+
+    if len == 18 && number.starts_with("1Z") && number.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return vec![Carrier::Ups];
+    }
+    if is_s10(number) {
+        return if number.ends_with("GB") {
+            vec![Carrier::RoyalMail]
+        } else {
+            vec![Carrier::Upu]
+        };
+    }
+    if len == 15 && number.starts_with("TBA") && all_digits(&number[3..]) {
+        return vec![Carrier::Amazon];
+    }
+    if (number.starts_with("JJD") && (13..=21).contains(&len) && all_digits(&number[3..]))
+        || (number.starts_with("GM") && (18..=20).contains(&len) && all_digits(&number[2..]))
+    {
+        return vec![Carrier::Dhl];
+    }
+    if !all_digits(number) {
+        return Vec::new();
+    }
+    match len {
+        10 => vec![Carrier::Dhl],
+        12 | 15 => vec![Carrier::FedEx],
+        20 if number.starts_with('9') => vec![Carrier::Usps, Carrier::FedEx],
+        20 => vec![Carrier::FedEx],
+        22 if number.starts_with('9') => vec![Carrier::Usps],
+        _ => Vec::new(),
+    }
+}
+
+/// Courier code hinting Ship24 at the carrier detected from the number alone. Bare 10 digit DHL
+/// waybills get none, since one phone or order number in seven passes their check digit.
+pub fn detected_courier_code(number: &str) -> Option<&'static str> {
+    match detect_carrier(number)? {
+        Carrier::Dhl if all_digits(number) => None,
+        carrier => carrier.ship24_code(number),
+    }
+}
+
+/// Detects the carrier of an uppercase, separator-free tracking number whose check digit holds.
+pub fn detect_carrier(number: &str) -> Option<Carrier> {
+    match validate(number) {
+        Validation::Valid(carrier) => Some(carrier),
+        Validation::Invalid(_) | Validation::Unrecognized => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn courier_hints() {
+        assert_eq!(detected_courier_code("1Z999AA10123456784"), Some("ups"));
+        assert_eq!(detected_courier_code("9400111899223197428497"), Some("us-post"));
+        assert_eq!(detected_courier_code("1234567891"), None);
+        assert_eq!(detected_courier_code("JJD000390007882215"), None);
+        assert_eq!(Carrier::Dhl.ship24_code("1234567891"), Some("dhl"));
+        assert_eq!(Carrier::Dhl.ship24_code("GM2951173225174494"), None);
+    }
+}
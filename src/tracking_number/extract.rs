@@ -0,0 +1,131 @@
+use super::normalize::is_url;
+use super::validate::{validate_input_line, Validation};
+use super::{detect_carrier, Carrier};
+
+/// Longest run of space or dash separated groups joined into one candidate, e.g. "1Z 999 AA1 01 2345 6784".
+const MAX_GROUPS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub number: String,
+    pub carrier: Carrier,
+}
+
+/// Whether the text already holds one tracking number per line, as typed in the input field,
+/// including numbers written in spaced groups such as "1z 999 aa1 0123456784". A single word
+/// counts when it has a digit or is a link, so "Hello" is not taken for a number.
+pub fn is_number_list(text: &str) -> bool {
+    text.lines().map(str::trim).all(|line| {
+        let single_word = !line.contains(char::is_whitespace)
+            && (line.contains(|c: char| c.is_ascii_digit()) || is_url(line));
+        line.is_empty() || single_word || validate_input_line(line) != Validation::Unrecognized
+    })
+}
+
+/// Splits text into alphanumeric words, recording whether each word is only separated from
+/// the next one by a single space or dash, so grouped numbers can be joined back together.
+fn words(text: &str) -> Vec<(String, bool)> {
+    let mut words: Vec<(String, bool)> = Vec::new();
+    let mut current = String::new();
+    let mut separator = String::new();
+
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            if current.is_empty() {
+                if let Some((_, joinable)) = words.last_mut() {
+                    *joinable = separator == " " || separator == "-";
+                }
+                separator.clear();
+            }
+            current.push(c.to_ascii_uppercase());
+        } else {
+            if !current.is_empty() {
+                words.push((std::mem::take(&mut current), false));
+            }
+            separator.push(c);
+        }
+    }
+    if !current.is_empty() {
+        words.push((current, false));
+    }
+    words
+}
+
+/// Scans free text, such as a pasted shipping confirmation, for tracking numbers.
+pub fn extract_tracking_numbers(text: &str) -> Vec<Candidate> {
+    let words = words(text);
+    let mut candidates: Vec<Candidate> = Vec::new();
+
+    let mut start = 0;
+    while start < words.len() {
+        let mut joined = String::new();
+        let mut found: Option<(usize, Candidate)> = None;
+
+        for (offset, (word, joinable)) in words[start..].iter().take(MAX_GROUPS).enumerate() {
+            joined.push_str(word);
+            if let Some(carrier) = detect_carrier(&joined) {
+                found = Some((offset + 1, Candidate { number: joined.clone(), carrier }));
+            }
+            if !joinable {
+                break;
+            }
+        }
+
+        match found {
+            Some((consumed, candidate)) => {
+                if !candidates.iter().any(|c| c.number == candidate.number) {
+                    candidates.push(candidate);
+                }
+                start += consumed;
+            }
+            None => start += 1,
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(text: &str) -> Vec<(String, Carrier)> {
+        extract_tracking_numbers(text)
+            .into_iter()
+            .map(|candidate| (candidate.number, candidate.carrier))
+            .collect()
+    }
+
+    #[test]
+    fn finds_numbers_in_free_text() {
+        let text = "Your UPS package 1Z 999 AA1 01 2345 6784 ships today.\nRoyal Mail: RR123456785GB";
+        assert_eq!(
+            numbers(text),
+            vec![
+                ("1Z999AA10123456784".to_string(), Carrier::Ups),
+                ("RR123456785GB".to_string(), Carrier::RoyalMail),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_numbers_are_found_once() {
+        let text = "Tracking: rr123456785gb (RR-123456785-GB)";
+        assert_eq!(numbers(text), vec![("RR123456785GB".to_string(), Carrier::RoyalMail)]);
+    }
+
+    #[test]
+    fn numbers_with_a_failed_check_digit_are_skipped() {
+        assert!(numbers("Order 12345 shipped with 1Z999AA10123456785").is_empty());
+    }
+
+    #[test]
+    fn number_lists() {
+        assert!(is_number_list("1Z999AA10123456784\nRR123456785GB"));
+        assert!(is_number_list("1z 999 aa1 0123456784"));
+        assert!(is_number_list("ABC12345\n\n1Z999AA10123456784"));
+        assert!(!is_number_list("Your package 1Z999AA10123456784 shipped"));
+        assert!(!is_number_list("Hello"));
+        assert!(is_number_list("https://example.com/track"));
+        assert!(!is_number_list("1Z999AA10123456784\nThanks"));
+    }
+}
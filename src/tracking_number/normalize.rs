@@ -0,0 +1,87 @@
+use super::format_matches;
+use super::url::parse_tracking_url;
+
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%' && i + 2 < bytes.len())
+            .then(|| std::str::from_utf8(&bytes[i + 1..i + 3]).ok())
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (escaped, bytes[i]) {
+            (Some(byte), _) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (None, b'+') => decoded.push(b' '),
+            (None, byte) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+pub fn is_url(input: &str) -> bool {
+    input.contains("://") || input.starts_with("www.")
+}
+
+/// Canonical form used for deduplication and storage: tracking links replaced by their number,
+/// whitespace and dashes removed, and uppercased when the number follows a known carrier format.
+/// Links of unknown sites are only trimmed.
+pub fn normalize(input: &str) -> String {
+    let input = input.trim().trim_matches(|c| c == '<' || c == '>');
+    if is_url(input) {
+        return parse_tracking_url(input).map_or_else(|| input.to_string(), |url| url.number);
+    }
+    let compact: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+
+    let uppercase = compact.to_uppercase();
+    if !format_matches(&uppercase).is_empty() {
+        uppercase
+    } else {
+        compact
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_escapes_and_plus() {
+        assert_eq!(percent_decode("RR%20123+456"), "RR 123 456");
+        assert_eq!(percent_decode("1Z%2c2"), "1Z,2");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn separators_are_removed_and_known_formats_uppercased() {
+        assert_eq!(normalize(" 1z 999 aa1-0123456784 "), "1Z999AA10123456784");
+        assert_eq!(normalize("rr123456785gb"), "RR123456785GB");
+        assert_eq!(normalize("abc-123"), "abc123");
+    }
+
+    #[test]
+    fn known_links_are_replaced_by_their_number() {
+        assert_eq!(
+            normalize("<https://www.ups.com/track?tracknum=1z999aa10123456784>"),
+            "1Z999AA10123456784"
+        );
+    }
+
+    #[test]
+    fn unknown_links_are_left_unchanged() {
+        assert_eq!(
+            normalize(" https://shop-example.com/orders/1Z999AA10123456784 "),
+            "https://shop-example.com/orders/1Z999AA10123456784"
+        );
+        assert_eq!(normalize("www.example.com/?id=123"), "www.example.com/?id=123");
+    }
+}
@@ -0,0 +1,236 @@
+use super::normalize::{is_url, normalize, percent_decode};
+use super::{detect_carrier, Carrier};
+
+struct TrackingSite {
+    host: &'static str,
+    carrier: Option<Carrier>,
+    /// Query parameters holding the number, matched case-insensitively.
+    keys: &'static [&'static str],
+    /// Path segment directly followed by the number.
+    path_marker: Option<&'static str>,
+}
+
+const TRACKING_SITES: [TrackingSite; 8] = [
+    TrackingSite {
+        host: "ups.com",
+        carrier: Some(Carrier::Ups),
+        keys: &["tracknum", "tracknums", "inquirynumber1"],
+        path_marker: None,
+    },
+    TrackingSite {
+        host: "usps.com",
+        carrier: Some(Carrier::Usps),
+        keys: &["tlabels", "qtc_tlabels1"],
+        path_marker: None,
+    },
+    TrackingSite {
+        host: "fedex.com",
+        carrier: Some(Carrier::FedEx),
+        keys: &["trknbr", "tracknumbers", "tracknums"],
+        path_marker: None,
+    },
+    TrackingSite {
+        host: "dhl.com",
+        carrier: Some(Carrier::Dhl),
+        keys: &["tracking-id", "awb", "piececode", "trackingnumber"],
+        path_marker: None,
+    },
+    TrackingSite {
+        host: "dhl.de",
+        carrier: Some(Carrier::Dhl),
+        keys: &["piececode", "idc", "tracking-id"],
+        path_marker: None,
+    },
+    TrackingSite {
+        host: "royalmail.com",
+        carrier: Some(Carrier::RoyalMail),
+        keys: &["tracknumber"],
+        path_marker: Some("tracking-results"),
+    },
+    TrackingSite {
+        host: "ship24.com",
+        carrier: None,
+        keys: &["p"],
+        path_marker: None,
+    },
+    TrackingSite {
+        host: "17track.net",
+        carrier: None,
+        keys: &["nums"],
+        path_marker: None,
+    },
+];
+
+pub struct TrackingUrl {
+    pub number: String,
+    pub carrier: Option<Carrier>,
+}
+
+/// Lines of the input with tracking URLs replaced by their numbers.
+pub struct ResolvedInput {
+    pub text: String,
+    /// Carriers named by the URL itself, to pass on as courier hints.
+    pub carriers: Vec<(String, Carrier)>,
+    pub unrecognized: Vec<String>,
+}
+
+fn host(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = rest
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('@')
+        .next()
+        .unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default().to_lowercase();
+    host.strip_prefix("www.").map(str::to_string).unwrap_or(host)
+}
+
+fn find_number(url: &str, site: &TrackingSite) -> Option<String> {
+    // Single page apps such as Royal Mail keep the number in the fragment, search it as well.
+    for part in url.split(['?', '#']).skip(1) {
+        for pair in part.split('&') {
+            let Some((key, value)) = pair.split_once('=') else {
+                continue;
+            };
+            if site.keys.contains(&key.to_lowercase().as_str()) && !value.is_empty() {
+                let value = percent_decode(value);
+                return value.split(',').next().map(str::to_string);
+            }
+        }
+    }
+
+    let marker = site.path_marker?;
+    let mut segments = url.split(['/', '?', '#', '&']);
+    segments.find(|segment| segment.eq_ignore_ascii_case(marker))?;
+    segments.find(|segment| !segment.is_empty()).map(percent_decode)
+}
+
+/// Recognizes tracking links of common carriers and of Ship24 itself.
+pub fn parse_tracking_url(url: &str) -> Option<TrackingUrl> {
+    let host = host(url.trim());
+    let site = TRACKING_SITES
+        .iter()
+        .find(|site| host == site.host || host.ends_with(&format!(".{}", site.host)))?;
+
+    let number = normalize(&find_number(url.trim(), site)?);
+    if number.is_empty() {
+        return None;
+    }
+    let carrier = site.carrier.or_else(|| detect_carrier(&number));
+    Some(TrackingUrl { number, carrier })
+}
+
+/// Replaces every line holding a tracking URL by its number, collecting the links it cannot read.
+pub fn resolve_urls(text: &str) -> ResolvedInput {
+    let mut lines = Vec::new();
+    let mut carriers = Vec::new();
+    let mut unrecognized = Vec::new();
+
+    for line in text.lines() {
+        if !is_url(line.trim()) {
+            lines.push(line.to_string());
+            continue;
+        }
+        match parse_tracking_url(line) {
+            Some(tracking_url) => {
+                if let Some(carrier) = tracking_url.carrier {
+                    carriers.push((tracking_url.number.clone(), carrier));
+                }
+                lines.push(tracking_url.number);
+            }
+            None => unrecognized.push(line.trim().to_string()),
+        }
+    }
+
+    ResolvedInput {
+        text: lines.join("\n"),
+        carriers,
+        unrecognized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(url: &str) -> Option<(String, Option<Carrier>)> {
+        parse_tracking_url(url).map(|url| (url.number, url.carrier))
+    }
+
+    #[test]
+    fn every_tracking_site_is_recognized() {
+        let cases = [
+            (
+                "https://www.ups.com/track?loc=en_US&tracknum=1Z999AA10123456784",
+                "1Z999AA10123456784",
+                Some(Carrier::Ups),
+            ),
+            (
+                "https://tools.usps.com/go/TrackConfirmAction?tLabels=9400111899223197428497",
+                "9400111899223197428497",
+                Some(Carrier::Usps),
+            ),
+            (
+                "https://www.fedex.com/fedextrack/?trknbr=123456789012",
+                "123456789012",
+                Some(Carrier::FedEx),
+            ),
+            (
+                "https://www.dhl.com/us-en/home/tracking/tracking-express.html?submit=1&tracking-id=1234567891",
+                "1234567891",
+                Some(Carrier::Dhl),
+            ),
+            (
+                "https://www.dhl.de/de/privatkunden/pakete-empfangen/verfolgen.html?piececode=JJD000390007882215",
+                "JJD000390007882215",
+                Some(Carrier::Dhl),
+            ),
+            (
+                "https://www.royalmail.com/track-your-item#/tracking-results/RR123456785GB",
+                "RR123456785GB",
+                Some(Carrier::RoyalMail),
+            ),
+            (
+                "https://www.ship24.com/tracking?p=RR123456785US",
+                "RR123456785US",
+                Some(Carrier::Upu),
+            ),
+            (
+                "https://t.17track.net/en#nums=1Z999AA10123456784,RR123456785GB",
+                "1Z999AA10123456784",
+                Some(Carrier::Ups),
+            ),
+        ];
+        assert_eq!(cases.len(), TRACKING_SITES.len());
+        for (url, number, carrier) in cases {
+            assert_eq!(parsed(url), Some((number.to_string(), carrier)), "{}", url);
+        }
+    }
+
+    #[test]
+    fn query_values_are_decoded_and_normalized() {
+        assert_eq!(
+            parsed("https://www.ups.com/track?TRACKNUM=1z999aa1%200123456784"),
+            Some(("1Z999AA10123456784".to_string(), Some(Carrier::Ups)))
+        );
+    }
+
+    #[test]
+    fn unknown_sites_and_missing_numbers_are_not_recognized() {
+        assert_eq!(parsed("https://example.com/track?tracknum=1Z999AA10123456784"), None);
+        assert_eq!(parsed("https://www.ups.com/track?loc=en_US"), None);
+        assert_eq!(parsed("https://notups.com/track?tracknum=1Z999AA10123456784"), None);
+    }
+
+    #[test]
+    fn resolve_urls_replaces_known_links() {
+        let resolved = resolve_urls(
+            "https://www.fedex.com/fedextrack/?trknbr=123456789012\nRR123456785GB\nhttps://example.com/1",
+        );
+        assert_eq!(resolved.text, "123456789012\nRR123456785GB");
+        assert_eq!(resolved.carriers, vec![("123456789012".to_string(), Carrier::FedEx)]);
+        assert_eq!(resolved.unrecognized, vec!["https://example.com/1".to_string()]);
+    }
+}
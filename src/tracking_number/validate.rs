@@ -0,0 +1,167 @@
+use super::normalize::normalize;
+use super::{all_digits, format_matches, Carrier};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Validation {
+    Valid(Carrier),
+    /// The number has this carrier's prefix or letters but its check digit does not match.
+    Invalid(Carrier),
+    /// No known format, Ship24 may still know the carrier.
+    Unrecognized,
+}
+
+fn digit_values(digits: &str) -> Vec<u32> {
+    digits.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+fn split_check_digit(number: &str) -> Option<(&str, u32)> {
+    let (body, check) = number.split_at(number.len().checked_sub(1)?);
+    Some((body, check.chars().next()?.to_digit(10)?))
+}
+
+/// UPS 1Z numbers: letters count as `(ascii - 63) % 10` and every other character is doubled.
+fn ups_check(number: &str) -> bool {
+    let Some((body, check)) = split_check_digit(&number[2..]) else {
+        return false;
+    };
+    let sum: u32 = body
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let value = c.to_digit(10).unwrap_or_else(|| (c as u32 - 63) % 10);
+            if i % 2 == 1 {
+                value * 2
+            } else {
+                value
+            }
+        })
+        .sum();
+    (10 - sum % 10) % 10 == check
+}
+
+/// GS1 style mod 10, used by USPS IMpb and FedEx Ground: weights 3 and 1 from the right.
+fn mod10_check(number: &str) -> bool {
+    let Some((body, check)) = split_check_digit(number) else {
+        return false;
+    };
+    let sum: u32 = digit_values(body)
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, value)| if i % 2 == 0 { value * 3 } else { *value })
+        .sum();
+    (10 - sum % 10) % 10 == check
+}
+
+/// FedEx Express 12 digit numbers: weights 1, 3, 7 from the right, mod 11.
+fn fedex_express_check(number: &str) -> bool {
+    let Some((body, check)) = split_check_digit(number) else {
+        return false;
+    };
+    let sum: u32 = digit_values(body)
+        .iter()
+        .rev()
+        .zip([1, 3, 7].iter().cycle())
+        .map(|(value, weight)| value * weight)
+        .sum();
+    sum % 11 % 10 == check
+}
+
+/// DHL Express 10 digit waybills: the check digit is the rest of the first nine modulo 7.
+fn dhl_check(number: &str) -> bool {
+    let Some((body, check)) = split_check_digit(number) else {
+        return false;
+    };
+    body.parse::<u64>().is_ok_and(|value| (value % 7) as u32 == check)
+}
+
+/// UPU S10: weights 8, 6, 4, 2, 3, 5, 9, 7 over the serial number, mod 11.
+fn s10_check(number: &str) -> bool {
+    let digits = digit_values(&number[2..11]);
+    let sum: u32 = digits[..8]
+        .iter()
+        .zip([8, 6, 4, 2, 3, 5, 9, 7])
+        .map(|(value, weight)| value * weight)
+        .sum();
+    let check = match 11 - sum % 11 {
+        10 => 0,
+        11 => 5,
+        check => check,
+    };
+    digits[8] == check
+}
+
+fn check_digit_matches(number: &str, carrier: Carrier) -> bool {
+    match carrier {
+        Carrier::Ups => ups_check(number),
+        Carrier::Usps => mod10_check(number),
+        Carrier::FedEx if number.len() == 12 => fedex_express_check(number),
+        Carrier::FedEx => mod10_check(number),
+        Carrier::Dhl if number.len() == 10 => dhl_check(number),
+        Carrier::RoyalMail | Carrier::Upu => s10_check(number),
+        Carrier::Dhl | Carrier::Amazon => true,
+    }
+}
+
+/// Recognizes the format of an uppercase, separator-free tracking number and verifies its check digit.
+/// Digit-only numbers are only told apart by length, which other carriers share, so a failed check
+/// digit leaves them unrecognized rather than invalid.
+pub fn validate(number: &str) -> Validation {
+    let carriers = format_matches(number);
+    match carriers.iter().find(|&&carrier| check_digit_matches(number, carrier)) {
+        Some(&carrier) => Validation::Valid(carrier),
+        None if all_digits(number) => Validation::Unrecognized,
+        None => carriers
+            .first()
+            .map_or(Validation::Unrecognized, |&carrier| Validation::Invalid(carrier)),
+    }
+}
+
+/// Validates a line of the input field once normalized.
+pub fn validate_input_line(line: &str) -> Validation {
+    if line.trim().is_empty() {
+        return Validation::Unrecognized;
+    }
+    validate(&normalize(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_numbers() {
+        assert_eq!(validate("1Z999AA10123456784"), Validation::Valid(Carrier::Ups));
+        assert_eq!(validate("RR123456785GB"), Validation::Valid(Carrier::RoyalMail));
+        assert_eq!(validate("RR123456785US"), Validation::Valid(Carrier::Upu));
+        assert_eq!(validate("9400111899223197428497"), Validation::Valid(Carrier::Usps));
+        assert_eq!(validate("123456789012"), Validation::Valid(Carrier::FedEx));
+        assert_eq!(validate("1234567891"), Validation::Valid(Carrier::Dhl));
+        assert_eq!(validate("TBA123456789012"), Validation::Valid(Carrier::Amazon));
+    }
+
+    #[test]
+    fn failed_check_digit_with_carrier_prefix_is_invalid() {
+        assert_eq!(validate("1Z999AA10123456785"), Validation::Invalid(Carrier::Ups));
+        assert_eq!(validate("RR123456784GB"), Validation::Invalid(Carrier::RoyalMail));
+    }
+
+    #[test]
+    fn failed_check_digit_with_digits_only_is_unrecognized() {
+        assert_eq!(validate("9400111899223197428490"), Validation::Unrecognized);
+        assert_eq!(validate("123456789013"), Validation::Unrecognized);
+        assert_eq!(validate("1234567890"), Validation::Unrecognized);
+    }
+
+    #[test]
+    fn unknown_formats_are_unrecognized() {
+        assert_eq!(validate("ABC123"), Validation::Unrecognized);
+        assert_eq!(validate("12345"), Validation::Unrecognized);
+    }
+
+    #[test]
+    fn input_lines_are_normalized() {
+        assert_eq!(validate_input_line(" 1z999aa1-0123456784 "), Validation::Valid(Carrier::Ups));
+        assert_eq!(validate_input_line("   "), Validation::Unrecognized);
+    }
+}